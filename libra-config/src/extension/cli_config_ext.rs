@@ -23,6 +23,7 @@ pub trait CliConfigExt {
         mode: ConfigSearchMode,
     ) -> Result<Option<ProfileConfig>>;
     fn save_ext(&self) -> CliTypedResult<()>;
+    fn save_in_ext(&self, mode: ConfigSearchMode) -> CliTypedResult<()>;
 }
 
 impl CliConfigExt for CliConfig {
@@ -84,7 +85,12 @@ impl CliConfigExt for CliConfig {
 
     /// Saves the config to ./.0L/config.yaml
     fn save_ext(&self) -> CliTypedResult<()> {
-        let _0l_folder = _0l_folder(ConfigSearchMode::CurrentDir)?;
+        self.save_in_ext(ConfigSearchMode::CurrentDir)
+    }
+
+    /// Saves the config where `load_ext` finds it with the same mode
+    fn save_in_ext(&self, mode: ConfigSearchMode) -> CliTypedResult<()> {
+        let _0l_folder = _0l_folder(mode)?;
 
         // Create if it doesn't exist
         create_dir_if_not_exist(_0l_folder.as_path())?;
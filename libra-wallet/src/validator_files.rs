@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use zapatos_genesis::config::{HostAndPort, OperatorConfiguration, OwnerConfiguration};
 
 use crate::{
@@ -14,6 +14,16 @@ pub const DEFAULT_VALIDATOR_DIR: &str = ".libra";
 pub const OPERATOR_FILE: &str = "operator.yaml";
 pub const OWNER_FILE: &str = "owner.yaml";
 
+/// The given directory, or the default one under $HOME
+pub fn validator_home_dir(home_dir: Option<PathBuf>) -> Result<PathBuf> {
+    match home_dir {
+        Some(dir) => Ok(dir),
+        None => dirs::home_dir()
+            .map(|dir| dir.join(DEFAULT_VALIDATOR_DIR))
+            .context("Unable to determine home directory"),
+    }
+}
+
 // copied from crate/aptos/src/genesis/keys.rs
 pub struct SetValidatorConfiguration {
     /// Name of the validator
@@ -46,8 +56,7 @@ impl SetValidatorConfiguration {
     }
 
     pub fn set_config_files(self) -> Result<(OperatorConfiguration, OwnerConfiguration)> {
-        let home_dir = self.home_dir
-          .unwrap_or_else(|| dirs::home_dir().unwrap().join(DEFAULT_VALIDATOR_DIR));
+        let home_dir = validator_home_dir(self.home_dir)?;
 
       let owner_keys_file  =home_dir.join(PUBLIC_KEYS_FILE);
 
@@ -141,7 +150,7 @@ impl SetValidatorConfiguration {
     pub fn read_configs_from_file(
         home_path: Option<PathBuf>,
     ) -> Result<(OperatorConfiguration, OwnerConfiguration)> {
-        let dir = home_path.unwrap_or_else(|| dirs::home_dir().unwrap().join(DEFAULT_VALIDATOR_DIR));
+        let dir = validator_home_dir(home_path)?;
 
         let operator_config: OperatorConfiguration = from_yaml(
             &String::from_utf8(read_from_file(&dir.join(OPERATOR_FILE)).unwrap()).unwrap(),
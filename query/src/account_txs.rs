@@ -0,0 +1,102 @@
+//! paginated transaction history of an account
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::str::FromStr;
+use zapatos_sdk::{
+    rest_client::{
+        aptos_api_types::{EntryFunctionId, Transaction, TransactionPayload},
+        Client,
+    },
+    types::account_address::AccountAddress,
+};
+
+/// Max number of transactions the REST API returns per page
+pub const TXS_PAGE_SIZE: u64 = 100;
+/// Number of transactions returned when no count is given
+pub const DEFAULT_TXS_COUNT: u64 = 25;
+
+/// Compact summary of a committed user transaction
+#[derive(Debug, Serialize)]
+pub struct TxSummary {
+    pub version: u64,
+    pub hash: String,
+    pub sender: String,
+    pub sequence_number: u64,
+    /// `None` for script and module payloads
+    pub entry_function: Option<String>,
+    pub success: bool,
+    pub gas_used: u64,
+    pub timestamp_usecs: u64,
+}
+
+impl TxSummary {
+    /// Returns `None` for transactions which are not user transactions
+    pub fn from_transaction(tx: &Transaction) -> Option<Self> {
+        match tx {
+            Transaction::UserTransaction(user_tx) => Some(Self {
+                version: user_tx.info.version.0,
+                hash: user_tx.info.hash.to_string(),
+                sender: user_tx.request.sender.to_string(),
+                sequence_number: user_tx.request.sequence_number.0,
+                entry_function: entry_function_id(&user_tx.request.payload).map(|f| f.to_string()),
+                success: user_tx.info.success,
+                gas_used: user_tx.info.gas_used.0,
+                timestamp_usecs: user_tx.timestamp.0,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Get the transactions sent by an account, starting from the sequence number `start`
+/// (defaults to the first one), optionally keeping only calls to the entry function `txs_type`.
+pub async fn get_account_txs(
+    client: &Client,
+    account: AccountAddress,
+    start: Option<u64>,
+    count: Option<u64>,
+    txs_type: Option<&str>,
+) -> Result<Vec<TxSummary>> {
+    let filter = txs_type
+        .map(|f| EntryFunctionId::from_str(f).context(format!("Invalid function id: {f}")))
+        .transpose()?;
+    let count = count.unwrap_or(DEFAULT_TXS_COUNT);
+
+    let mut summaries = vec![];
+    let mut start = start.unwrap_or_default();
+    while (summaries.len() as u64) < count {
+        let page = client
+            .get_account_transactions(account, Some(start), Some(TXS_PAGE_SIZE))
+            .await
+            .context("Failed to get account transactions")?
+            .into_inner();
+        let page_len = page.len() as u64;
+
+        for tx in &page {
+            if let Transaction::UserTransaction(user_tx) = tx {
+                start = user_tx.request.sequence_number.0 + 1;
+                let is_match = match &filter {
+                    Some(f) => entry_function_id(&user_tx.request.payload) == Some(f),
+                    None => true,
+                };
+                if is_match {
+                    summaries.extend(TxSummary::from_transaction(tx));
+                }
+            }
+        }
+
+        if page_len < TXS_PAGE_SIZE {
+            break;
+        }
+    }
+    summaries.truncate(count as usize);
+    Ok(summaries)
+}
+
+fn entry_function_id(payload: &TransactionPayload) -> Option<&EntryFunctionId> {
+    match payload {
+        TransactionPayload::EntryFunctionPayload(p) => Some(&p.function),
+        _ => None,
+    }
+}
@@ -0,0 +1,85 @@
+//! balance of an account split by 0L slow wallet locks and stake
+
+use crate::extension::client_ext::ClientExt;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use zapatos_sdk::{
+    coin_client::CoinClient, rest_client::Client, types::account_address::AccountAddress,
+};
+
+pub const SLOW_WALLET: &str = "0x1::slow_wallet::SlowWallet";
+pub const STAKE_POOL: &str = "0x1::stake::StakePool";
+
+#[derive(Debug, Serialize)]
+pub struct BalanceBreakdown {
+    /// coins in the CoinStore
+    pub total: u64,
+    /// coins which can be transferred
+    pub unlocked: u64,
+    /// coins still locked in the slow wallet
+    pub locked: u64,
+    pub is_slow_wallet: bool,
+    /// coins transferred out of the slow wallet so far
+    pub transferred: Option<u64>,
+    /// coins held in the stake pool of a validator account
+    pub stake: Option<StakeBalance>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StakeBalance {
+    pub active: u64,
+    pub inactive: u64,
+    pub pending_active: u64,
+    pub pending_inactive: u64,
+}
+
+/// Get the total, unlocked and transferred amounts of an account, and any stake it holds
+pub async fn get_balance_breakdown(
+    client: &Client,
+    account: AccountAddress,
+) -> Result<BalanceBreakdown> {
+    let total = CoinClient::new(client)
+        .get_account_balance(&account)
+        .await
+        .context("Failed to get account balance")?;
+
+    let slow_wallet = client.get_resource_data_ext(account, SLOW_WALLET).await?;
+    let (unlocked, transferred) = match &slow_wallet {
+        Some(data) => (
+            // the unlocked tracker can exceed what is left in the account
+            parse_u64(data, "/unlocked")?.min(total),
+            Some(parse_u64(data, "/transferred")?),
+        ),
+        // accounts which are not slow wallets are fully unlocked
+        None => (total, None),
+    };
+
+    let stake = match client.get_resource_data_ext(account, STAKE_POOL).await? {
+        Some(data) => Some(StakeBalance {
+            active: parse_u64(&data, "/active/value")?,
+            inactive: parse_u64(&data, "/inactive/value")?,
+            pending_active: parse_u64(&data, "/pending_active/value")?,
+            pending_inactive: parse_u64(&data, "/pending_inactive/value")?,
+        }),
+        None => None,
+    };
+
+    Ok(BalanceBreakdown {
+        total,
+        unlocked,
+        locked: total - unlocked,
+        is_slow_wallet: slow_wallet.is_some(),
+        transferred,
+        stake,
+    })
+}
+
+/// Move u64 values are encoded as strings in the REST json
+fn parse_u64(data: &Value, pointer: &str) -> Result<u64> {
+    data.pointer(pointer)
+        .and_then(|v| v.as_str())
+        .context(format!("Field {pointer} not found"))?
+        .parse()
+        .context(format!("Field {pointer} is not a u64"))
+}
@@ -0,0 +1,48 @@
+//! summary of the chain state as seen by the connected node
+
+use crate::extension::client_ext::ClientExt;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
+
+pub const VALIDATOR_SET: &str = "0x1::stake::ValidatorSet";
+
+#[derive(Debug, Serialize)]
+pub struct ChainStatus {
+    pub chain_id: u8,
+    pub epoch: u64,
+    pub ledger_version: u64,
+    pub block_height: u64,
+    pub ledger_timestamp_usecs: u64,
+    pub node_role: String,
+    /// number of active validators in the current epoch
+    pub validator_set_size: usize,
+}
+
+/// Get the chain status from the REST index and `0x1::stake::ValidatorSet`
+pub async fn get_chain_status(client: &Client) -> Result<ChainStatus> {
+    let index = client
+        .get_index()
+        .await
+        .context("Failed to get the ledger info")?
+        .into_inner();
+    let validator_set = client
+        .get_resource_data_ext(AccountAddress::ONE, VALIDATOR_SET)
+        .await?
+        .context("Validator set not found")?;
+    let validator_set_size = validator_set
+        .get("active_validators")
+        .and_then(|v| v.as_array())
+        .context("Validator set has no active validators")?
+        .len();
+
+    Ok(ChainStatus {
+        chain_id: index.chain_id,
+        epoch: index.epoch.0,
+        ledger_version: index.ledger_version.0,
+        block_height: index.block_height.0,
+        ledger_timestamp_usecs: index.ledger_timestamp.0,
+        node_role: index.node_role.to_string(),
+        validator_set_size,
+    })
+}
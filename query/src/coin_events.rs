@@ -0,0 +1,163 @@
+//! sent and received coin transfers of an account, from the CoinStore event handles
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{collections::HashMap, future::Future, time::Duration};
+use zapatos_sdk::{
+    rest_client::{
+        aptos_api_types::{Transaction, TransactionPayload, VersionedEvent},
+        error::RestError,
+        Client, Response,
+    },
+    types::account_address::AccountAddress,
+};
+
+pub const COIN_STORE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+/// `(module, function)` of the `0x1` entry functions which send coins to their first argument
+const TRANSFER_FUNCTIONS: &[(&str, &str)] = &[
+    ("coin", "transfer"),
+    ("aptos_account", "transfer"),
+    ("aptos_account", "transfer_coins"),
+];
+/// Tries for each request before giving up
+const LOOKUP_ATTEMPTS: u64 = 3;
+/// Events requested per page
+pub const EVENTS_PAGE_SIZE: u16 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    /// coins withdrawn from the account
+    Sent,
+    /// coins deposited to the account
+    Received,
+}
+
+impl TransferDirection {
+    fn event_handle(&self) -> &'static str {
+        match self {
+            TransferDirection::Sent => "withdraw_events",
+            TransferDirection::Received => "deposit_events",
+        }
+    }
+}
+
+/// A coin withdraw or deposit of an account
+#[derive(Debug, Serialize)]
+pub struct TransferEvent {
+    pub sequence_number: u64,
+    pub version: u64,
+    pub direction: TransferDirection,
+    pub amount: u64,
+    /// sender of a received transfer, or recipient of a sent transfer, when the transaction
+    /// which emitted the event is a known transfer function, otherwise unknown
+    pub counterparty: Option<String>,
+}
+
+/// Get all the transfer events of an account starting from the event sequence number
+/// `seq_start`, page by page
+pub async fn get_transfer_events(
+    client: &Client,
+    account: AccountAddress,
+    direction: TransferDirection,
+    seq_start: Option<u64>,
+) -> Result<Vec<TransferEvent>> {
+    let mut events = vec![];
+    let mut start = seq_start;
+    loop {
+        let page = with_retry("Failed to get account events", || {
+            client.get_account_events(
+                account,
+                COIN_STORE,
+                direction.event_handle(),
+                start,
+                Some(EVENTS_PAGE_SIZE),
+            )
+        })
+        .await?;
+        let page_len = page.len();
+        if let Some(last) = page.last() {
+            start = Some(last.sequence_number.0 + 1);
+        }
+        events.extend(page);
+        // a short page is the last one
+        if page_len < EVENTS_PAGE_SIZE as usize {
+            break;
+        }
+    }
+
+    // events emitted by the same transaction share one lookup
+    let mut counterparties: HashMap<u64, Option<String>> = HashMap::new();
+    let mut transfers = vec![];
+    for event in events {
+        let version = event.version.0;
+        if !counterparties.contains_key(&version) {
+            let transaction = with_retry(&format!("Failed to get transaction {version}"), || {
+                client.get_transaction_by_version(version)
+            })
+            .await?;
+            counterparties.insert(version, counterparty(&transaction, direction));
+        }
+        transfers.push(TransferEvent {
+            sequence_number: event.sequence_number.0,
+            version,
+            direction,
+            amount: event_amount(&event)?,
+            counterparty: counterparties[&version].to_owned(),
+        });
+    }
+    Ok(transfers)
+}
+
+fn event_amount(event: &VersionedEvent) -> Result<u64> {
+    Ok(event
+        .data
+        .get("amount")
+        .and_then(|a| a.as_str())
+        .context(format!("Event has no amount: {}", event.data))?
+        .parse()?)
+}
+
+/// Retry a request a few times, so that one failed request does not stop `--follow`
+async fn with_retry<T, F, Fut>(what: &str, request: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Response<T>, RestError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Ok(res) => return Ok(res.into_inner()),
+            Err(_) if attempt < LOOKUP_ATTEMPTS => {
+                tokio::time::sleep(Duration::from_secs(attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err).context(what.to_owned()),
+        }
+    }
+}
+
+/// The other account of a transfer, only decoded from the entry functions known to move
+/// coins from the sender to their first argument. Unknown for anything else.
+fn counterparty(transaction: &Transaction, direction: TransferDirection) -> Option<String> {
+    let user_tx = match transaction {
+        Transaction::UserTransaction(user_tx) => user_tx,
+        _ => return None,
+    };
+    let function = match &user_tx.request.payload {
+        TransactionPayload::EntryFunctionPayload(p) => p,
+        _ => return None,
+    };
+    let is_transfer = *function.function.module.address.inner() == AccountAddress::ONE
+        && TRANSFER_FUNCTIONS.contains(&(
+            function.function.module.name.0.as_str(),
+            function.function.name.0.as_str(),
+        ));
+    if !is_transfer {
+        return None;
+    }
+    match direction {
+        TransferDirection::Received => Some(user_tx.request.sender.to_string()),
+        TransferDirection::Sent => function.arguments.first()?.as_str().map(|s| s.to_owned()),
+    }
+}
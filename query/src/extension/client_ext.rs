@@ -1,19 +1,64 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
+use std::collections::BTreeMap;
+use zapatos_sdk::{
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode},
+        error::{AptosErrorResponse, RestError},
+        Client,
+    },
+    types::account_address::AccountAddress,
+};
 
 #[async_trait]
 pub trait ClientExt {
-    async fn get_account_resources_ext(&self, account: AccountAddress) -> Result<String>;
+    /// Returns the json data of all resources of an account, keyed by resource type
+    async fn get_account_resources_ext(
+        &self,
+        account: AccountAddress,
+    ) -> Result<BTreeMap<String, serde_json::Value>>;
+
+    /// Returns the json data of a resource, or `None` if the account does not hold it
+    async fn get_resource_data_ext(
+        &self,
+        account: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<serde_json::Value>>;
 }
 
 #[async_trait]
 impl ClientExt for Client {
-    async fn get_account_resources_ext(&self, account: AccountAddress) -> Result<String> {
+    async fn get_account_resources_ext(
+        &self,
+        account: AccountAddress,
+    ) -> Result<BTreeMap<String, serde_json::Value>> {
         let response = self
             .get_account_resources(account)
             .await
             .context("Failed to get account resources")?;
-        Ok(format!("{:#?}", response.inner()))
+        Ok(response
+            .into_inner()
+            .into_iter()
+            .map(|res| (res.resource_type.to_string(), res.data))
+            .collect())
+    }
+
+    async fn get_resource_data_ext(
+        &self,
+        account: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        match self.get_account_resource(account, resource_type).await {
+            Ok(response) => Ok(response.into_inner().map(|res| res.data)),
+            Err(RestError::Api(AptosErrorResponse {
+                error:
+                    AptosError {
+                        error_code: AptosErrorCode::ResourceNotFound,
+                        ..
+                    },
+                ..
+            })) => Ok(None),
+            Err(err) => Err(err).context(format!("Failed to get account resource {resource_type}")),
+        }
     }
 }
@@ -1,2 +1,11 @@
+pub mod account_txs;
+pub mod balance;
+pub mod chain_status;
+pub mod coin_events;
 pub mod extension;
+pub mod move_value;
+pub mod output;
 pub mod querier;
+pub mod query_result;
+pub mod sync_delay;
+pub mod val_config;
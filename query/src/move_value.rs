@@ -0,0 +1,88 @@
+//! look up a field of any move resource by its path
+
+use crate::extension::client_ext::ClientExt;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use zapatos_sdk::{
+    move_types::parser::parse_struct_tag, rest_client::Client,
+    types::account_address::AccountAddress,
+};
+
+/// Build the full resource type from a module and struct name.
+/// The module defaults to the `0x1` address when it is not given, e.g. `coin` or `0x1::coin`,
+/// and the struct may be type parameterised, e.g. `CoinStore<0x1::aptos_coin::AptosCoin>`.
+pub fn resource_type(module_name: &str, struct_name: &str) -> Result<String> {
+    let module = if module_name.contains("::") {
+        module_name.to_owned()
+    } else {
+        format!("0x1::{module_name}")
+    };
+    let resource_type = format!("{module}::{struct_name}");
+    let struct_tag = parse_struct_tag(&resource_type)
+        .context(format!("Invalid resource type: {resource_type}"))?;
+    Ok(struct_tag.to_string())
+}
+
+/// Extract a nested value by a dotted path (`coin.value`, `vouches.0`) or
+/// a JSON pointer (`/coin/value`). An empty path returns the whole value.
+pub fn extract_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') {
+        return value.pointer(path);
+    }
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |v, key| match v {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => v.get(key),
+        })
+}
+
+/// Get the value at `path` of a resource held by `account`
+pub async fn get_move_value(
+    client: &Client,
+    account: AccountAddress,
+    module_name: &str,
+    struct_name: &str,
+    path: &str,
+) -> Result<Value> {
+    let resource_type = resource_type(module_name, struct_name)?;
+    let data = client
+        .get_resource_data_ext(account, &resource_type)
+        .await?
+        .context(format!(
+            "Resource {resource_type} not found for account {}",
+            account.to_hex_literal()
+        ))?;
+    extract_path(&data, path)
+        .cloned()
+        .context(format!("Path {path} not found in {resource_type}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_path, resource_type};
+    use serde_json::json;
+
+    #[test]
+    fn extract_dotted_and_pointer_paths() {
+        let data = json!({ "coin": { "value": "100" }, "vouches": ["0x1", "0x2"] });
+        assert_eq!(Some(&json!("100")), extract_path(&data, "coin.value"));
+        assert_eq!(Some(&json!("100")), extract_path(&data, "/coin/value"));
+        assert_eq!(Some(&json!("0x2")), extract_path(&data, "vouches.1"));
+        assert_eq!(Some(&data), extract_path(&data, ""));
+        assert_eq!(None, extract_path(&data, "coin.missing"));
+    }
+
+    #[test]
+    fn build_type_parameterised_resource_type() {
+        assert_eq!(
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            resource_type("coin", "CoinStore<0x1::aptos_coin::AptosCoin>").unwrap()
+        );
+        assert_eq!(
+            "0x1::account::Account",
+            resource_type("0x1::account", "Account").unwrap()
+        );
+        assert!(resource_type("coin", "CoinStore<").is_err());
+    }
+}
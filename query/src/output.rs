@@ -0,0 +1,117 @@
+//! render query and transaction results as json, yaml or a plain text table
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty printed json
+    Json,
+    /// Yaml document
+    Yaml,
+    /// Human readable table
+    #[default]
+    Table,
+}
+
+/// Serializes any result into the requested output format
+pub fn format_output<T: Serialize>(value: &T, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value)?,
+        OutputFormat::Yaml => serde_yaml::to_string(value)?,
+        OutputFormat::Table => format_table(&serde_json::to_value(value)?),
+    })
+}
+
+fn format_table(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let rows = map
+                .iter()
+                .map(|(k, v)| vec![k.to_owned(), format_cell(v)])
+                .collect::<Vec<_>>();
+            render_rows(&rows)
+        }
+        Value::Array(items) if !items.is_empty() && items.iter().all(|i| i.is_object()) => {
+            let mut header: Vec<String> = vec![];
+            for item in items {
+                for key in item.as_object().into_iter().flat_map(|m| m.keys()) {
+                    if !header.contains(key) {
+                        header.push(key.to_owned());
+                    }
+                }
+            }
+            let mut rows = vec![header.iter().map(|h| h.to_uppercase()).collect::<Vec<_>>()];
+            for item in items {
+                rows.push(
+                    header
+                        .iter()
+                        .map(|h| item.get(h).map(format_cell).unwrap_or_default())
+                        .collect(),
+                );
+            }
+            render_rows(&rows)
+        }
+        Value::Array(items) => items.iter().map(format_cell).collect::<Vec<_>>().join("\n"),
+        other => format_cell(other),
+    }
+}
+
+fn format_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.to_owned(),
+        other => other.to_string(),
+    }
+}
+
+fn render_rows(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or_default();
+    let widths = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| format!("{:width$}", cell, width = widths[i]))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_output, OutputFormat};
+    use serde_json::json;
+
+    #[test]
+    fn format_object_as_table() {
+        let value = json!({ "account": "0x1", "balance": 100 });
+        let table = format_output(&value, OutputFormat::Table).unwrap();
+        assert_eq!("account  0x1\nbalance  100", table);
+    }
+
+    #[test]
+    fn format_list_of_objects_as_table() {
+        let value = json!([
+            { "success": true, "version": 1 },
+            { "success": false, "version": 22 },
+        ]);
+        let table = format_output(&value, OutputFormat::Table).unwrap();
+        assert_eq!("SUCCESS  VERSION\ntrue     1\nfalse    22", table);
+    }
+}
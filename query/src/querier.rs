@@ -1,13 +1,23 @@
-use crate::extension::client_ext::ClientExt;
-use anyhow::Result;
-use zapatos_sdk::{
-    coin_client::CoinClient, rest_client::Client, types::account_address::AccountAddress,
+use crate::{
+    account_txs::get_account_txs,
+    balance::get_balance_breakdown,
+    chain_status::get_chain_status,
+    coin_events::{get_transfer_events, TransferDirection},
+    extension::client_ext::ClientExt,
+    move_value::get_move_value,
+    query_result::QueryResult,
+    sync_delay::get_sync_delay,
+    val_config::get_val_config,
 };
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+use url::Url;
+use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
 use QueryType::*;
 
 #[derive(Debug)]
 pub enum QueryType {
-    /// Account balance
+    /// Account balance, with the unlocked, transferred and staked amounts
     Balance {
         /// account to query txs of
         account: AccountAddress,
@@ -21,6 +31,8 @@ pub enum QueryType {
     Epoch,
     /// Network block height
     BlockHeight,
+    /// Chain id, epoch, ledger version, block height and validator set size
+    Status,
     /// All account resources
     Resources {
         /// account to query txs of
@@ -30,24 +42,27 @@ pub enum QueryType {
     MoveValue {
         /// account to query txs of
         account: AccountAddress,
-        /// move module name
+        /// move module name, with an optional address: `coin` or `0x1::coin`
         module_name: String,
-        /// move struct name
+        /// move struct name, with optional type parameters: `CoinStore<0x1::aptos_coin::AptosCoin>`
         struct_name: String,
-        /// move key name
+        /// dotted path or JSON pointer of the field, empty for the whole struct
         key_name: String,
     },
     /// How far behind the local is from the upstream nodes
-    SyncDelay,
+    SyncDelay {
+        /// REST urls of the upstream nodes
+        upstreams: Vec<Url>,
+    },
     /// Get transaction history
     Txs {
         /// account to query txs of
         account: AccountAddress,
-        /// get transactions after this height
+        /// get transactions starting from this sequence number of the account
         txs_height: Option<u64>,
         /// limit how many txs
         txs_count: Option<u64>,
-        /// filter by type
+        /// filter by entry function id, e.g. 0x1::coin::transfer
         txs_type: Option<String>,
     },
     /// Get events
@@ -63,6 +78,8 @@ pub enum QueryType {
     ValConfig {
         /// the account of the validator
         account: AccountAddress,
+        /// directory of the local operator.yaml and public-keys.yaml to compare against
+        local_dir: Option<PathBuf>,
     },
 }
 
@@ -75,18 +92,82 @@ impl Querier {
         Self { client }
     }
 
-    pub async fn query(&self, query_type: QueryType) -> Result<String> {
-        let print = match query_type {
+    pub async fn query(&self, query_type: QueryType) -> Result<QueryResult> {
+        let result = match query_type {
             Balance { account } => {
-                let coin_client = CoinClient::new(&self.client);
-                coin_client.get_account_balance(&account).await?.to_string()
+                QueryResult::Balance(get_balance_breakdown(&self.client, account).await?)
+            }
+            UnlockedBalance { account } => QueryResult::UnlockedBalance {
+                unlocked: get_balance_breakdown(&self.client, account).await?.unlocked,
+            },
+            Epoch => {
+                let index = self.client.get_index().await?.into_inner();
+                QueryResult::Epoch {
+                    epoch: index.epoch.0,
+                }
+            }
+            BlockHeight => {
+                let index = self.client.get_index().await?.into_inner();
+                QueryResult::BlockHeight {
+                    block_height: index.block_height.0,
+                }
+            }
+            Status => QueryResult::Status(get_chain_status(&self.client).await?),
+            Resources { account } => {
+                QueryResult::Resources(self.client.get_account_resources_ext(account).await?)
+            }
+            MoveValue {
+                account,
+                module_name,
+                struct_name,
+                key_name,
+            } => QueryResult::MoveValue(
+                get_move_value(&self.client, account, &module_name, &struct_name, &key_name)
+                    .await?,
+            ),
+            SyncDelay { upstreams } => {
+                if upstreams.is_empty() {
+                    bail!("SyncDelay needs at least one upstream node to compare against");
+                }
+                QueryResult::SyncDelay(get_sync_delay(&self.client, &upstreams).await?)
+            }
+            Txs {
+                account,
+                txs_height,
+                txs_count,
+                txs_type,
+            } => QueryResult::Txs(
+                get_account_txs(
+                    &self.client,
+                    account,
+                    txs_height,
+                    txs_count,
+                    txs_type.as_deref(),
+                )
+                .await?,
+            ),
+            Events {
+                account,
+                sent_or_received,
+                seq_start,
+            } => {
+                let direction = if sent_or_received {
+                    TransferDirection::Sent
+                } else {
+                    TransferDirection::Received
+                };
+                QueryResult::Events(
+                    get_transfer_events(&self.client, account, direction, seq_start).await?,
+                )
             }
-            Resources { account } => self.client.get_account_resources_ext(account).await?,
-            _ => {
-                //TODO: Implement other types of Query
-                String::new()
+            ValConfig { account, local_dir } => {
+                let mut config = get_val_config(&self.client, account).await?;
+                if let Some(dir) = local_dir {
+                    config.compare_with_local(&dir)?;
+                }
+                QueryResult::ValConfig(config)
             }
         };
-        Ok(print)
+        Ok(result)
     }
 }
@@ -1,7 +1,15 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
 use libra_config::extension::client_ext::ClientExt;
-use query::querier::{Querier, QueryType::*};
+use libra_wallet::validator_files::validator_home_dir;
+use query::{
+    coin_events::{get_transfer_events, TransferDirection},
+    output::{format_output, OutputFormat},
+    querier::{Querier, QueryType::*},
+    sync_delay::get_sync_delay,
+};
+use std::{path::PathBuf, time::Duration};
+use url::Url;
 use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
 
 #[derive(Parser)]
@@ -9,6 +17,10 @@ use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
 pub struct QueryCli {
     #[clap(subcommand)]
     subcommand: Option<Subcommand>,
+
+    /// Output format of the query result
+    #[clap(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
 }
 
 #[derive(clap::Subcommand)]
@@ -20,12 +32,124 @@ enum Subcommand {
         account_address: String,
     },
 
+    /// Get the unlocked balance of an account
+    UnlockedBalance {
+        /// Address of the onchain account to get unlocked balance from
+        #[clap(short, long)]
+        account_address: String,
+    },
+
+    /// Get the current epoch
+    Epoch,
+
+    /// Get the current block height of the network
+    BlockHeight,
+
+    /// Get the chain id, epoch, ledger version, block height and validator set size
+    ///
+    /// The waypoint is not shown: the node's REST API does not expose it, since it is part
+    /// of the node's configuration and genesis files rather than of the ledger state.
+    Status,
+
     /// Get all resources of an account
     AccountResources {
         /// Address of the onchain account to get resources from
         #[clap(short, long)]
         account_address: String,
     },
+
+    /// Get a move value from a resource of an account
+    MoveValue {
+        /// Address of the onchain account holding the resource
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Move module name, optionally with its address, e.g. coin or 0x1::coin
+        #[clap(short, long)]
+        module_name: String,
+
+        /// Move struct name, optionally with type parameters, e.g. 'CoinStore<0x1::aptos_coin::AptosCoin>'
+        #[clap(short, long)]
+        struct_name: String,
+
+        /// Dotted path (coin.value) or JSON pointer (/coin/value) of the field to get.
+        /// Gets the whole struct when not given
+        #[clap(short, long)]
+        key_name: Option<String>,
+    },
+
+    /// Get how far behind the local node is from the upstream nodes.
+    /// Exits with an error when the lag is over one of the thresholds
+    SyncDelay {
+        /// REST url of an upstream node, can be repeated
+        #[clap(short, long = "upstream", required = true)]
+        upstreams: Vec<Url>,
+
+        /// Maximum accepted lag in seconds
+        #[clap(long)]
+        max_lag_secs: Option<i64>,
+
+        /// Maximum accepted lag in versions
+        #[clap(long)]
+        max_lag_versions: Option<i64>,
+    },
+
+    /// Get the transaction history of an account
+    Txs {
+        /// Address of the onchain account to get transactions of
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Get transactions starting from this sequence number of the account
+        #[clap(long)]
+        txs_height: Option<u64>,
+
+        /// Maximum number of transactions to get, defaults to 25
+        #[clap(long)]
+        txs_count: Option<u64>,
+
+        /// Only get transactions calling this entry function, e.g. 0x1::coin::transfer
+        #[clap(long)]
+        txs_type: Option<String>,
+    },
+
+    /// Get the coins sent or received by an account
+    Events {
+        /// Address of the onchain account to get events of
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Get sent (withdraw) events instead of received (deposit) events
+        #[clap(long)]
+        sent: bool,
+
+        /// Event sequence number to start querying from
+        #[clap(long)]
+        seq_start: Option<u64>,
+
+        /// Keep polling for new events
+        #[clap(long)]
+        follow: bool,
+
+        /// Seconds to wait between polls when following events
+        #[clap(long, default_value_t = 10)]
+        interval_secs: u64,
+    },
+
+    /// Get the onchain configuration of a validator
+    ValConfig {
+        /// Address of the validator account
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Compare the onchain config with the local operator.yaml and public-keys.yaml
+        #[clap(long)]
+        compare_local: bool,
+
+        /// Directory of the local validator files, defaults to $HOME/.libra
+        #[clap(long)]
+        home_dir: Option<PathBuf>,
+    },
 }
 
 impl QueryCli {
@@ -33,19 +157,115 @@ impl QueryCli {
         let client = Client::default()?;
         let querier = Querier::new(client);
 
-        match &self.subcommand {
-            Some(Subcommand::AccountBalance { account_address }) => {
-                let account = AccountAddress::from_hex_literal(account_address)?;
-                let balance = querier.query(Balance { account }).await?;
-                println!("Account balance: {balance} coins");
+        let query_type = match &self.subcommand {
+            Some(Subcommand::AccountBalance { account_address }) => Balance {
+                account: AccountAddress::from_hex_literal(account_address)?,
+            },
+            Some(Subcommand::UnlockedBalance { account_address }) => UnlockedBalance {
+                account: AccountAddress::from_hex_literal(account_address)?,
+            },
+            Some(Subcommand::Epoch) => Epoch,
+            Some(Subcommand::BlockHeight) => BlockHeight,
+            Some(Subcommand::Status) => Status,
+            Some(Subcommand::AccountResources { account_address }) => Resources {
+                account: AccountAddress::from_hex_literal(account_address)?,
+            },
+            Some(Subcommand::MoveValue {
+                account_address,
+                module_name,
+                struct_name,
+                key_name,
+            }) => MoveValue {
+                account: AccountAddress::from_hex_literal(account_address)?,
+                module_name: module_name.to_owned(),
+                struct_name: struct_name.to_owned(),
+                key_name: key_name.to_owned().unwrap_or_default(),
+            },
+            Some(Subcommand::SyncDelay {
+                upstreams,
+                max_lag_secs,
+                max_lag_versions,
+            }) => {
+                let report = get_sync_delay(&querier.client, upstreams).await?;
+                println!("{}", format_output(&report, self.output)?);
+
+                if report.upstreams.iter().all(|u| u.error.is_some()) {
+                    bail!("None of the upstream nodes could be reached");
+                }
+                if let (Some(max), Some(lag)) = (max_lag_secs, report.max_lag_secs()) {
+                    if lag > *max {
+                        bail!("Local node is {lag} seconds behind, over the threshold of {max}");
+                    }
+                }
+                if let (Some(max), Some(lag)) = (max_lag_versions, report.max_lag_versions()) {
+                    if lag > *max {
+                        bail!("Local node is {lag} versions behind, over the threshold of {max}");
+                    }
+                }
+                return Ok(());
             }
-            Some(Subcommand::AccountResources { account_address }) => {
+            Some(Subcommand::Txs {
+                account_address,
+                txs_height,
+                txs_count,
+                txs_type,
+            }) => Txs {
+                account: AccountAddress::from_hex_literal(account_address)?,
+                txs_height: txs_height.to_owned(),
+                txs_count: txs_count.to_owned(),
+                txs_type: txs_type.to_owned(),
+            },
+            Some(Subcommand::Events {
+                account_address,
+                sent,
+                seq_start,
+                follow: true,
+                interval_secs,
+            }) => {
                 let account = AccountAddress::from_hex_literal(account_address)?;
-                let resources = querier.query(Resources { account }).await?;
-                println!("{resources}");
+                let direction = if *sent {
+                    TransferDirection::Sent
+                } else {
+                    TransferDirection::Received
+                };
+                let mut seq_start = seq_start.to_owned();
+                loop {
+                    let events =
+                        get_transfer_events(&querier.client, account, direction, seq_start).await?;
+                    if let Some(last) = events.last() {
+                        seq_start = Some(last.sequence_number + 1);
+                        println!("{}", format_output(&events, self.output)?);
+                    }
+                    tokio::time::sleep(Duration::from_secs(*interval_secs)).await;
+                }
             }
-            _ => { /* do nothing */ }
-        }
+            Some(Subcommand::Events {
+                account_address,
+                sent,
+                seq_start,
+                ..
+            }) => Events {
+                account: AccountAddress::from_hex_literal(account_address)?,
+                sent_or_received: *sent,
+                seq_start: seq_start.to_owned(),
+            },
+            Some(Subcommand::ValConfig {
+                account_address,
+                compare_local,
+                home_dir,
+            }) => ValConfig {
+                account: AccountAddress::from_hex_literal(account_address)?,
+                local_dir: if *compare_local {
+                    Some(validator_home_dir(home_dir.to_owned())?)
+                } else {
+                    None
+                },
+            },
+            _ => return Ok(()),
+        };
+
+        let result = querier.query(query_type).await?;
+        println!("{}", format_output(&result, self.output)?);
         Ok(())
     }
 }
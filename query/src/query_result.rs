@@ -0,0 +1,34 @@
+use crate::{
+    account_txs::TxSummary, balance::BalanceBreakdown, chain_status::ChainStatus,
+    coin_events::TransferEvent, sync_delay::SyncReport, val_config::ValConfigView,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Typed result of a `QueryType`, serializable for machine readable output
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum QueryResult {
+    /// Account balance split by slow wallet locks and stake
+    Balance(BalanceBreakdown),
+    /// Unlocked account balance
+    UnlockedBalance { unlocked: u64 },
+    /// Current epoch
+    Epoch { epoch: u64 },
+    /// Network block height
+    BlockHeight { block_height: u64 },
+    /// Summary of the chain state
+    Status(ChainStatus),
+    /// Account resources keyed by their resource type
+    Resources(BTreeMap<String, serde_json::Value>),
+    /// A move value from an account resource
+    MoveValue(serde_json::Value),
+    /// Lag of the local node behind the upstream nodes
+    SyncDelay(SyncReport),
+    /// Transaction history
+    Txs(Vec<TxSummary>),
+    /// Coin transfer events
+    Events(Vec<TransferEvent>),
+    /// Validator onchain configuration
+    ValConfig(ValConfigView),
+}
@@ -0,0 +1,88 @@
+//! how far behind the local node is from upstream nodes
+
+use anyhow::{Context, Result};
+use libra_config::extension::client_ext::{DEFAULT_TIMEOUT_SECS, USER_AGENT};
+use serde::Serialize;
+use std::time::Duration;
+use url::Url;
+use zapatos_sdk::rest_client::Client;
+
+/// Ledger state of the local node compared with each upstream node
+#[derive(Debug, Serialize)]
+pub struct SyncReport {
+    pub local_version: u64,
+    pub local_timestamp_usecs: u64,
+    pub upstreams: Vec<UpstreamDelay>,
+}
+
+/// Lag of the local node behind one upstream, negative if the local node is ahead
+#[derive(Debug, Serialize)]
+pub struct UpstreamDelay {
+    pub url: String,
+    pub ledger_version: Option<u64>,
+    pub lag_versions: Option<i64>,
+    pub lag_secs: Option<i64>,
+    /// set when the upstream could not be reached
+    pub error: Option<String>,
+}
+
+impl SyncReport {
+    /// Largest lag in versions among the reachable upstreams
+    pub fn max_lag_versions(&self) -> Option<i64> {
+        self.upstreams.iter().filter_map(|u| u.lag_versions).max()
+    }
+
+    /// Largest lag in seconds among the reachable upstreams
+    pub fn max_lag_secs(&self) -> Option<i64> {
+        self.upstreams.iter().filter_map(|u| u.lag_secs).max()
+    }
+}
+
+/// Compare the ledger version and timestamp of the local node with the upstream nodes
+pub async fn get_sync_delay(local: &Client, upstreams: &[Url]) -> Result<SyncReport> {
+    let local_index = local
+        .get_index()
+        .await
+        .context("Failed to get the ledger info of the local node")?
+        .into_inner();
+    let local_version = local_index.ledger_version.0;
+    let local_timestamp_usecs = local_index.ledger_timestamp.0;
+
+    let mut delays = vec![];
+    for url in upstreams {
+        let client = Client::new_with_timeout_and_user_agent(
+            url.to_owned(),
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            USER_AGENT,
+        );
+        let delay = match client.get_index().await {
+            Ok(index) => {
+                let index = index.into_inner();
+                UpstreamDelay {
+                    url: url.to_string(),
+                    ledger_version: Some(index.ledger_version.0),
+                    lag_versions: Some(index.ledger_version.0 as i64 - local_version as i64),
+                    lag_secs: Some(
+                        (index.ledger_timestamp.0 as i64 - local_timestamp_usecs as i64)
+                            / 1_000_000,
+                    ),
+                    error: None,
+                }
+            }
+            Err(err) => UpstreamDelay {
+                url: url.to_string(),
+                ledger_version: None,
+                lag_versions: None,
+                lag_secs: None,
+                error: Some(err.to_string()),
+            },
+        };
+        delays.push(delay);
+    }
+
+    Ok(SyncReport {
+        local_version,
+        local_timestamp_usecs,
+        upstreams: delays,
+    })
+}
@@ -0,0 +1,180 @@
+//! decode a validator's onchain config and compare it with the local validator files
+
+use anyhow::{Context, Result};
+use libra_wallet::{
+    keys::PUBLIC_KEYS_FILE,
+    utils::{from_yaml, read_from_file, read_public_identity_file},
+    validator_files::OPERATOR_FILE,
+};
+use serde::Serialize;
+use std::path::Path;
+use zapatos_genesis::config::OperatorConfiguration;
+use zapatos_sdk::{
+    crypto::ValidCryptoMaterialStringExt,
+    rest_client::Client,
+    types::{
+        account_address::AccountAddress, network_address::NetworkAddress,
+        validator_config::ValidatorConfig,
+    },
+};
+
+pub const VALIDATOR_CONFIG: &str = "0x1::stake::ValidatorConfig";
+
+/// Human readable form of `0x1::stake::ValidatorConfig`
+#[derive(Debug, Serialize)]
+pub struct ValConfigView {
+    pub account: String,
+    pub consensus_public_key: String,
+    pub validator_index: u64,
+    pub validator_network_addresses: Vec<DecodedAddress>,
+    pub fullnode_network_addresses: Vec<DecodedAddress>,
+    /// comparison with the local operator.yaml and public-keys.yaml, if requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub local_checks: Vec<ConfigCheck>,
+}
+
+/// A network address decoded from its BCS bytes
+#[derive(Debug, Serialize)]
+pub struct DecodedAddress {
+    pub multiaddr: String,
+    /// x25519 key of the noise protocol
+    pub network_public_key: Option<String>,
+}
+
+/// One field of the onchain config compared with the local files
+#[derive(Debug, Serialize)]
+pub struct ConfigCheck {
+    pub field: String,
+    pub onchain: String,
+    pub local: String,
+    pub matches: bool,
+}
+
+impl ConfigCheck {
+    fn new(field: &str, onchain: String, local: String) -> Self {
+        Self {
+            field: field.to_owned(),
+            matches: onchain == local,
+            onchain,
+            local,
+        }
+    }
+}
+
+/// Fetch and decode the validator config of an account
+pub async fn get_val_config(client: &Client, account: AccountAddress) -> Result<ValConfigView> {
+    let config = client
+        .get_account_resource_bcs::<ValidatorConfig>(account, VALIDATOR_CONFIG)
+        .await
+        .context(format!(
+            "Account {} has no validator config",
+            account.to_hex_literal()
+        ))?
+        .into_inner();
+
+    Ok(ValConfigView {
+        account: account.to_hex_literal(),
+        consensus_public_key: config.consensus_public_key.to_encoded_string()?,
+        validator_index: config.validator_index,
+        validator_network_addresses: decode_addresses(
+            config
+                .validator_network_addresses()
+                .context("Failed to decode validator network addresses")?,
+        )?,
+        fullnode_network_addresses: decode_addresses(
+            config
+                .fullnode_network_addresses()
+                .context("Failed to decode fullnode network addresses")?,
+        )?,
+        local_checks: vec![],
+    })
+}
+
+fn decode_addresses(addresses: Vec<NetworkAddress>) -> Result<Vec<DecodedAddress>> {
+    addresses
+        .into_iter()
+        .map(|addr| {
+            Ok(DecodedAddress {
+                network_public_key: addr
+                    .find_noise_proto()
+                    .map(|key| key.to_encoded_string())
+                    .transpose()?,
+                multiaddr: addr.to_string(),
+            })
+        })
+        .collect()
+}
+
+impl ValConfigView {
+    /// Compare with the operator.yaml and public-keys.yaml in `home_dir`
+    pub fn compare_with_local(&mut self, home_dir: &Path) -> Result<()> {
+        let operator_file = home_dir.join(OPERATOR_FILE);
+        let operator: OperatorConfiguration = from_yaml(&String::from_utf8(
+            read_from_file(&operator_file)
+                .context(format!("Failed to read {}", operator_file.display()))?,
+        )?)?;
+        let public_keys_file = home_dir.join(PUBLIC_KEYS_FILE);
+        let public_identity = read_public_identity_file(&public_keys_file)
+            .context(format!("Failed to read {}", public_keys_file.display()))?;
+
+        let mut checks = vec![
+            ConfigCheck::new(
+                "consensus_public_key",
+                self.consensus_public_key.to_owned(),
+                operator.consensus_public_key.to_encoded_string()?,
+            ),
+            ConfigCheck::new(
+                "validator_network_address",
+                join_multiaddrs(&self.validator_network_addresses),
+                operator
+                    .validator_host
+                    .as_network_address(operator.validator_network_public_key)?
+                    .to_string(),
+            ),
+        ];
+        if let Some(key) = public_identity.validator_network_public_key {
+            checks.push(ConfigCheck::new(
+                "validator_network_public_key",
+                join_keys(&self.validator_network_addresses),
+                key.to_encoded_string()?,
+            ));
+        }
+
+        if let (Some(host), Some(key)) = (
+            operator.full_node_host.as_ref(),
+            operator.full_node_network_public_key,
+        ) {
+            checks.push(ConfigCheck::new(
+                "fullnode_network_address",
+                join_multiaddrs(&self.fullnode_network_addresses),
+                host.as_network_address(key)?.to_string(),
+            ));
+        }
+        if let Some(key) = public_identity.full_node_network_public_key {
+            checks.push(ConfigCheck::new(
+                "fullnode_network_public_key",
+                join_keys(&self.fullnode_network_addresses),
+                key.to_encoded_string()?,
+            ));
+        }
+
+        self.local_checks = checks;
+        Ok(())
+    }
+}
+
+fn join_multiaddrs(addresses: &[DecodedAddress]) -> String {
+    addresses
+        .iter()
+        .map(|a| a.multiaddr.to_owned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn join_keys(addresses: &[DecodedAddress]) -> String {
+    addresses
+        .iter()
+        .filter_map(|a| a.network_public_key.to_owned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,336 @@
+//! check and encode function arguments against the module ABI published on chain
+
+use anyhow::{bail, Context, Result};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::str::FromStr;
+use zapatos_sdk::{
+    move_types::{account_address::AccountAddress, language_storage::TypeTag, u256::U256},
+    rest_client::{
+        aptos_api_types::{MoveFunction, MoveStructTag, MoveType},
+        Client,
+    },
+};
+
+/// Fetch the ABI of a function from the module published at its address
+pub async fn get_function_abi(
+    client: &Client,
+    address: AccountAddress,
+    module_name: &str,
+    function_name: &str,
+) -> Result<MoveFunction> {
+    let module = client
+        .get_account_module(address, module_name)
+        .await
+        .context(format!(
+            "Failed to get the module {}::{module_name}",
+            address.to_hex_literal()
+        ))?
+        .into_inner();
+    let abi = module
+        .abi
+        .context(format!("Module {module_name} has no ABI"))?;
+    abi.exposed_functions
+        .into_iter()
+        .find(|f| f.name.0.as_str() == function_name)
+        .context(format!(
+            "Function {function_name} not found in module {}::{module_name}",
+            address.to_hex_literal()
+        ))
+}
+
+/// Arguments are given either as a json array, or separated by commas
+pub fn split_args(args: Option<&str>) -> Result<Vec<Value>> {
+    let args = match args.map(str::trim) {
+        Some(args) if !args.is_empty() => args,
+        _ => return Ok(vec![]),
+    };
+    if args.starts_with('[') {
+        return serde_json::from_str(args).context("Arguments are not a valid json array");
+    }
+    Ok(args
+        .split(',')
+        .map(|arg| Value::String(arg.trim().to_owned()))
+        .collect())
+}
+
+/// Check the arguments against the function parameters, leaving out the signers of
+/// entry functions, and convert each one to its Move type
+pub fn typed_args(
+    function: &MoveFunction,
+    ty_args: &[TypeTag],
+    args: &[Value],
+) -> Result<Vec<MoveArg>> {
+    if function.generic_type_params.len() != ty_args.len() {
+        bail!(
+            "{} expects {} type argument(s), got {}",
+            function.name.0,
+            function.generic_type_params.len(),
+            ty_args.len()
+        );
+    }
+    let params: Vec<&MoveType> = function
+        .params
+        .iter()
+        .filter(|param| !is_signer(param))
+        .collect();
+    if params.len() != args.len() {
+        bail!(
+            "{} expects {} argument(s) ({}), got {}",
+            function.name.0,
+            params.len(),
+            params
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            args.len()
+        );
+    }
+
+    params
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(i, (param, arg))| {
+            let param = substitute(param, ty_args)?;
+            MoveArg::parse(&param, arg).context(format!("Argument {i} ({param}): {arg}"))
+        })
+        .collect()
+}
+
+fn is_signer(param: &MoveType) -> bool {
+    match param {
+        MoveType::Signer => true,
+        MoveType::Reference { to, .. } => matches!(**to, MoveType::Signer),
+        _ => false,
+    }
+}
+
+/// Replace generic type parameters with the type arguments given
+fn substitute(param: &MoveType, ty_args: &[TypeTag]) -> Result<MoveType> {
+    Ok(match param {
+        MoveType::GenericTypeParam { index } => ty_args
+            .get(*index as usize)
+            .context(format!("Missing type argument T{index}"))?
+            .into(),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, ty_args)?),
+        },
+        MoveType::Struct(tag) => MoveType::Struct(MoveStructTag {
+            generic_type_params: tag
+                .generic_type_params
+                .iter()
+                .map(|t| substitute(t, ty_args))
+                .collect::<Result<_>>()?,
+            ..tag.clone()
+        }),
+        other => other.clone(),
+    })
+}
+
+/// A function argument converted to its Move type
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveArg {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    U256(U256),
+    Address(AccountAddress),
+    String(String),
+    Bytes(Vec<u8>),
+    Vector(Vec<MoveArg>),
+    Option(Option<Box<MoveArg>>),
+}
+
+impl MoveArg {
+    pub fn parse(move_type: &MoveType, value: &Value) -> Result<Self> {
+        Ok(match move_type {
+            MoveType::Bool => Self::Bool(match value {
+                Value::Bool(b) => *b,
+                Value::String(s) => s.parse().context("Expected true or false")?,
+                _ => bail!("Expected a bool"),
+            }),
+            MoveType::U8 => Self::U8(parse_int(value)?),
+            MoveType::U16 => Self::U16(parse_int(value)?),
+            MoveType::U32 => Self::U32(parse_int(value)?),
+            MoveType::U64 => Self::U64(parse_int(value)?),
+            MoveType::U128 => Self::U128(parse_int(value)?),
+            MoveType::U256 => Self::U256(parse_int(value)?),
+            MoveType::Address => Self::Address(parse_address(value)?),
+            MoveType::Vector { items } if **items == MoveType::U8 => match value {
+                Value::String(s) => Self::Bytes(parse_bytes(s)?),
+                Value::Array(items) => {
+                    Self::Bytes(items.iter().map(parse_int).collect::<Result<_>>()?)
+                }
+                _ => bail!("Expected a hex string or an array of bytes"),
+            },
+            MoveType::Vector { items: item_type } => match value {
+                Value::Array(items) => Self::Vector(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| {
+                            Self::parse(item_type, item).context(format!("Element {i}: {item}"))
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                _ => bail!("Expected a json array"),
+            },
+            MoveType::Struct(tag) => Self::parse_struct(tag, value)?,
+            other => bail!("Arguments of type {other} are not supported"),
+        })
+    }
+
+    /// Only the framework structs which can be passed to entry functions
+    fn parse_struct(tag: &MoveStructTag, value: &Value) -> Result<Self> {
+        if tag.address.inner() != &AccountAddress::ONE {
+            bail!("Struct arguments are not supported");
+        }
+        match (tag.module.0.as_str(), tag.name.0.as_str()) {
+            ("string", "String") => match value {
+                Value::String(s) => Ok(Self::String(s.to_owned())),
+                _ => bail!("Expected a string"),
+            },
+            ("object", "Object") => Ok(Self::Address(parse_address(value)?)),
+            ("option", "Option") => {
+                let inner = tag
+                    .generic_type_params
+                    .first()
+                    .context("Option without a type")?;
+                match value {
+                    Value::Null => Ok(Self::Option(None)),
+                    value => Ok(Self::Option(Some(Box::new(Self::parse(inner, value)?)))),
+                }
+            }
+            _ => bail!("Struct arguments are not supported"),
+        }
+    }
+
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).context("Failed to encode argument")
+    }
+
+    /// The json form the REST api expects, with 64 bit and larger integers as strings
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Bool(b) => Value::Bool(*b),
+            Self::U8(n) => Value::from(*n),
+            Self::U16(n) => Value::from(*n),
+            Self::U32(n) => Value::from(*n),
+            Self::U64(n) => Value::String(n.to_string()),
+            Self::U128(n) => Value::String(n.to_string()),
+            Self::U256(n) => Value::String(n.to_string()),
+            Self::Address(a) => Value::String(a.to_hex_literal()),
+            Self::String(s) => Value::String(s.to_owned()),
+            Self::Bytes(b) => Value::String(format!("0x{}", hex::encode(b))),
+            Self::Vector(items) => Value::Array(items.iter().map(Self::to_json).collect()),
+            Self::Option(item) => serde_json::json!({
+                "vec": item.iter().map(|i| i.to_json()).collect::<Vec<_>>()
+            }),
+        }
+    }
+}
+
+/// BCS layout of the Move value
+impl Serialize for MoveArg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::U8(n) => serializer.serialize_u8(*n),
+            Self::U16(n) => serializer.serialize_u16(*n),
+            Self::U32(n) => serializer.serialize_u32(*n),
+            Self::U64(n) => serializer.serialize_u64(*n),
+            Self::U128(n) => serializer.serialize_u128(*n),
+            Self::U256(n) => n.serialize(serializer),
+            Self::Address(a) => a.serialize(serializer),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Bytes(b) => serializer.serialize_bytes(b),
+            Self::Vector(items) => items.serialize(serializer),
+            // an Option is a vector of zero or one element
+            Self::Option(item) => item.iter().collect::<Vec<_>>().serialize(serializer),
+        }
+    }
+}
+
+/// Accepts json numbers, and strings with an optional `_u64` style suffix
+fn parse_int<T: FromStr>(value: &Value) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let s = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_owned(),
+        _ => bail!("Expected a number"),
+    };
+    let digits = match s.rsplit_once("_u") {
+        Some((digits, _)) => digits,
+        None => &s,
+    };
+    digits
+        .parse()
+        .context(format!("{s} is not a valid number for this type"))
+}
+
+fn parse_address(value: &Value) -> Result<AccountAddress> {
+    match value {
+        Value::String(s) => AccountAddress::from_hex_literal(s.trim())
+            .or_else(|_| AccountAddress::from_str(s.trim()))
+            .context("Expected an address"),
+        _ => bail!("Expected an address string"),
+    }
+}
+
+/// Accepts `0x1234` and the `x"1234"` form of the comma separated arguments
+fn parse_bytes(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim();
+    let hex_str = s
+        .strip_prefix("x\"")
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| s.strip_prefix("0x"))
+        .unwrap_or(s);
+    hex::decode(hex_str).context("Expected hex bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn splits_json_and_comma_args() {
+        assert_eq!(
+            split_args(Some(r#"["a,b", [1, 2]]"#)).unwrap(),
+            vec![json!("a,b"), json!([1, 2])]
+        );
+        assert_eq!(
+            split_args(Some("0x1, 5")).unwrap(),
+            vec![json!("0x1"), json!("5")]
+        );
+        assert!(split_args(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_typed_args() {
+        let vec_u64 = MoveType::Vector {
+            items: Box::new(MoveType::U64),
+        };
+        let arg = MoveArg::parse(&vec_u64, &json!([1, "2"])).unwrap();
+        assert_eq!(arg, MoveArg::Vector(vec![MoveArg::U64(1), MoveArg::U64(2)]));
+        assert_eq!(
+            arg.to_bcs().unwrap(),
+            bcs::to_bytes(&vec![1u64, 2]).unwrap()
+        );
+        assert_eq!(arg.to_json(), json!(["1", "2"]));
+
+        assert_eq!(
+            MoveArg::parse(&MoveType::U8, &json!("24_u8")).unwrap(),
+            MoveArg::U8(24)
+        );
+        assert!(MoveArg::parse(&MoveType::U8, &json!(256)).is_err());
+        assert!(MoveArg::parse(&vec_u64, &json!("1")).is_err());
+    }
+}
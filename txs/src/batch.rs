@@ -0,0 +1,404 @@
+//! send many transfers or entry function calls from a manifest, keeping a resumable report
+
+use crate::{
+    extension::client_ext::{transaction_builder, transfer_payload, ClientExt, TransactionOptions},
+    vm_status::TxFailure,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode, Transaction},
+        error::{AptosErrorResponse, RestError},
+        Client,
+    },
+    types::{
+        account_address::AccountAddress,
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+
+/// A transfer when `to_account` and `amount` are set, otherwise an entry function call
+#[derive(Debug, Deserialize)]
+pub struct ManifestRow {
+    pub to_account: Option<String>,
+    pub amount: Option<u64>,
+    pub function_id: Option<String>,
+    pub type_args: Option<String>,
+    pub args: Option<String>,
+}
+
+impl ManifestRow {
+    /// Function calls are checked against the ABI of their module
+    pub async fn payload(&self, client: &Client) -> Result<TransactionPayload> {
+        match (&self.to_account, self.amount, &self.function_id) {
+            (Some(to_account), Some(amount), None) => {
+                let to_account = AccountAddress::from_hex_literal(to_account).context(format!(
+                    "Failed to parse the recipient address {to_account}"
+                ))?;
+                transfer_payload(to_account, amount)
+            }
+            (None, None, Some(function_id)) => {
+                client
+                    .entry_function_payload_ext(
+                        function_id,
+                        self.type_args.to_owned(),
+                        self.args.to_owned(),
+                    )
+                    .await
+            }
+            _ => bail!("A row needs either to_account and amount, or function_id"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    Pending,
+    Submitted,
+    Success,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowResult {
+    pub row: usize,
+    pub hash: Option<String>,
+    pub status: RowStatus,
+    pub error: Option<String>,
+}
+
+impl RowResult {
+    /// Rows which succeeded are never sent again, failed ones only when retrying
+    pub fn is_due(&self, retry_failed: bool) -> bool {
+        match self.status {
+            RowStatus::Success => false,
+            RowStatus::Failed => retry_failed,
+            RowStatus::Pending | RowStatus::Submitted => true,
+        }
+    }
+}
+
+/// How the rows of a batch are sent
+pub struct BatchOptions {
+    pub transaction: TransactionOptions,
+    /// Transactions submitted per batch request
+    pub batch_size: usize,
+    /// Wait for each batch to commit before submitting the next one
+    pub wait: bool,
+    /// Send the rows which failed on a previous run again
+    pub retry_failed: bool,
+}
+
+/// Read a manifest from a csv file with a header row, or from a json array
+pub fn read_manifest(path: &Path) -> Result<Vec<ManifestRow>> {
+    let is_csv = path.extension().map(|ext| ext == "csv").unwrap_or(false);
+    if is_csv {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .context(format!("Failed to read {}", path.display()))?;
+        reader
+            .deserialize()
+            .enumerate()
+            .map(|(i, row)| row.context(format!("Invalid manifest row {i}")))
+            .collect()
+    } else {
+        let bytes = std::fs::read(path).context(format!("Failed to read {}", path.display()))?;
+        serde_json::from_slice(&bytes).context(format!("Invalid json manifest {}", path.display()))
+    }
+}
+
+/// Load the report of a previous run, or start a new one with every row pending
+pub fn load_report(path: &Path, manifest_len: usize) -> Result<Vec<RowResult>> {
+    if !path.exists() {
+        return Ok((0..manifest_len)
+            .map(|row| RowResult {
+                row,
+                hash: None,
+                status: RowStatus::Pending,
+                error: None,
+            })
+            .collect());
+    }
+
+    let bytes = std::fs::read(path).context(format!("Failed to read {}", path.display()))?;
+    let report: Vec<RowResult> = serde_json::from_slice(&bytes)
+        .context(format!("Invalid batch report {}", path.display()))?;
+    if report.len() != manifest_len {
+        bail!(
+            "The report {} has {} rows but the manifest has {manifest_len}",
+            path.display(),
+            report.len()
+        );
+    }
+    Ok(report)
+}
+
+pub fn save_report(path: &Path, report: &[RowResult]) -> Result<()> {
+    std::fs::write(path, serde_json::to_vec_pretty(report)?)
+        .context(format!("Failed to write {}", path.display()))
+}
+
+/// Settle the rows a previous run submitted but did not see committed
+pub async fn reconcile_submitted(client: &Client, report: &mut [RowResult]) -> Result<()> {
+    for result in report
+        .iter_mut()
+        .filter(|r| r.status == RowStatus::Submitted)
+    {
+        let hash = match &result.hash {
+            Some(hash) => HashValue::from_hex_literal(hash)
+                .context(format!("Invalid hash in report row {}", result.row))?,
+            None => {
+                result.status = RowStatus::Pending;
+                continue;
+            }
+        };
+        let committed = match client.get_transaction_by_hash(hash).await {
+            Ok(res) => match res.into_inner() {
+                Transaction::UserTransaction(user_trans) if user_trans.info.success => Some(Ok(())),
+                Transaction::UserTransaction(user_trans) => {
+                    Some(Err(TxFailure::new(&user_trans).vm_status))
+                }
+                _ => bail!(
+                    "Row {} is still in the mempool, rerun once it is committed or expired",
+                    result.row
+                ),
+            },
+            Err(RestError::Api(AptosErrorResponse {
+                error:
+                    AptosError {
+                        error_code: AptosErrorCode::TransactionNotFound,
+                        ..
+                    },
+                ..
+            })) => None,
+            Err(err) => return Err(err).context(format!("Failed to look up row {}", result.row)),
+        };
+        settle(result, committed);
+    }
+    Ok(())
+}
+
+/// Update a submitted row with its committed outcome, or the vm status it failed with.
+/// A row which was never committed has expired, and is sent again.
+fn settle(result: &mut RowResult, committed: Option<Result<(), String>>) {
+    match committed {
+        Some(Ok(())) => {
+            result.status = RowStatus::Success;
+            result.error = None;
+        }
+        Some(Err(vm_status)) => {
+            result.status = RowStatus::Failed;
+            result.error = Some(vm_status);
+        }
+        None => result.status = RowStatus::Pending,
+    }
+}
+
+/// Sign every due row with locally incremented sequence numbers, and submit them
+/// `batch_size` at a time through the batch endpoint. The report is saved after each
+/// chunk, and the run stops at the first chunk which did not fully succeed, since the
+/// following sequence numbers would be out of order. Without `wait` the chunks are
+/// submitted back to back and the rows are left as submitted, for the next run to settle.
+pub async fn submit_batch(
+    client: &Client,
+    account: &mut LocalAccount,
+    manifest: &[ManifestRow],
+    report: &mut [RowResult],
+    report_path: &Path,
+    options: &BatchOptions,
+) -> Result<()> {
+    let chain_id = client.get_index().await?.inner().chain_id;
+
+    // check every row before using any sequence number
+    let mut pending = vec![];
+    for (row, result) in manifest.iter().zip(report.iter_mut()) {
+        if !result.is_due(options.retry_failed) {
+            continue;
+        }
+        match row.payload(client).await {
+            Ok(payload) => pending.push((result.row, payload)),
+            Err(err) => {
+                result.status = RowStatus::Failed;
+                result.error = Some(format!("{err:#}"));
+            }
+        }
+    }
+    save_report(report_path, report)?;
+
+    for chunk in pending.chunks(options.batch_size.max(1)) {
+        let signed: Vec<(usize, SignedTransaction)> = chunk
+            .iter()
+            .map(|(row, payload)| {
+                let builder = transaction_builder(payload.clone(), chain_id, &options.transaction);
+                (*row, account.sign_with_transaction_builder(builder))
+            })
+            .collect();
+        let transactions: Vec<SignedTransaction> =
+            signed.iter().map(|(_, trans)| trans.clone()).collect();
+
+        let failures = client
+            .submit_batch(&transactions)
+            .await
+            .context("Failed to submit the batch")?
+            .into_inner()
+            .transaction_failures;
+        // a rejected transaction leaves a gap in the sequence numbers
+        let mut out_of_order = !failures.is_empty();
+        for (index, (row, trans)) in signed.iter().enumerate() {
+            let result = &mut report[*row];
+            result.hash = Some(trans.clone().committed_hash().to_hex_literal());
+            match failures.iter().find(|f| f.transaction_index == index) {
+                Some(failure) => {
+                    result.status = RowStatus::Failed;
+                    result.error = Some(failure.error.message.to_owned());
+                }
+                None => {
+                    result.status = RowStatus::Submitted;
+                    result.error = None;
+                }
+            }
+        }
+        save_report(report_path, report)?;
+
+        for (row, trans) in &signed {
+            let result = &mut report[*row];
+            if !options.wait || result.status != RowStatus::Submitted {
+                continue;
+            }
+            match client.wait_for_signed_transaction_ext(trans).await {
+                Ok(_) => result.status = RowStatus::Success,
+                // aborted, but committed with its sequence number
+                Err(err) if err.downcast_ref::<TxFailure>().is_some() => {
+                    result.status = RowStatus::Failed;
+                    result.error = Some(format!("{err:#}"));
+                }
+                // left as submitted since it may still commit, the next run settles it
+                Err(err) => {
+                    out_of_order = true;
+                    result.error = Some(format!("{err:#}"));
+                }
+            }
+        }
+        save_report(report_path, report)?;
+
+        if out_of_order {
+            bail!(
+                "Some transactions were not committed, see {} and rerun to resume",
+                report_path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("txs-batch-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn submitted(row: usize) -> RowResult {
+        RowResult {
+            row,
+            hash: Some(HashValue::zero().to_hex_literal()),
+            status: RowStatus::Submitted,
+            error: Some("timed out".to_owned()),
+        }
+    }
+
+    #[test]
+    fn reads_csv_manifest() {
+        let path = temp_file(
+            "manifest.csv",
+            "to_account,amount,function_id,type_args,args\n\
+             0x1, 10,,,\n\
+             ,,0x1::coin::transfer,0x1::aptos_coin::AptosCoin,\"0x2, 5\"\n",
+        );
+        let manifest = read_manifest(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(manifest[0].to_account.as_deref(), Some("0x1"));
+        assert_eq!(manifest[0].amount, Some(10));
+        assert!(manifest[0].function_id.is_none());
+        assert_eq!(
+            manifest[1].function_id.as_deref(),
+            Some("0x1::coin::transfer")
+        );
+        assert_eq!(manifest[1].args.as_deref(), Some("0x2, 5"));
+    }
+
+    #[test]
+    fn reads_json_manifest() {
+        let path = temp_file(
+            "manifest.json",
+            r#"[
+                {"to_account": "0x1", "amount": 10},
+                {"function_id": "0x1::coin::transfer", "args": "[\"0x2\", 5]"}
+            ]"#,
+        );
+        let manifest = read_manifest(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(manifest[0].amount, Some(10));
+        assert_eq!(manifest[1].args.as_deref(), Some(r#"["0x2", 5]"#));
+        assert!(manifest[1].type_args.is_none());
+    }
+
+    #[test]
+    fn loads_new_and_existing_reports() {
+        let missing = std::env::temp_dir().join("txs-batch-missing-report.json");
+        let report = load_report(&missing, 3).unwrap();
+        assert_eq!(report.len(), 3);
+        assert!(report.iter().all(|r| r.status == RowStatus::Pending));
+
+        let path = temp_file("report.json", "");
+        save_report(&path, &[submitted(0), submitted(1)]).unwrap();
+        let report = load_report(&path, 2).unwrap();
+        assert_eq!(report[1].status, RowStatus::Submitted);
+        // written for another manifest
+        assert!(load_report(&path, 3).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn settles_submitted_rows() {
+        let mut row = submitted(0);
+        settle(&mut row, Some(Ok(())));
+        assert_eq!(row.status, RowStatus::Success);
+        assert!(row.error.is_none());
+
+        let mut row = submitted(1);
+        settle(&mut row, Some(Err("EINSUFFICIENT_BALANCE".to_owned())));
+        assert_eq!(row.status, RowStatus::Failed);
+        assert_eq!(row.error.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+
+        // expired, sent again on this run
+        let mut row = submitted(2);
+        settle(&mut row, None);
+        assert_eq!(row.status, RowStatus::Pending);
+    }
+
+    #[test]
+    fn skips_failed_rows_unless_retrying() {
+        let mut row = submitted(0);
+        assert!(row.is_due(false));
+        row.status = RowStatus::Failed;
+        assert!(!row.is_due(false));
+        assert!(row.is_due(true));
+        row.status = RowStatus::Success;
+        assert!(!row.is_due(true));
+    }
+}
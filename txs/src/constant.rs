@@ -1,2 +1,22 @@
 pub const DEFAULT_MAX_GAS_AMOUNT: u64 = 5_000;
 pub const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+/// Seconds a transaction stays valid after it is signed, unless `--expiration-secs` is given
+pub const DEFAULT_EXPIRATION_SECS: u64 = 30;
+/// Offline signed transactions need time to travel between the online and the air-gapped machine
+pub const DEFAULT_OFFLINE_EXPIRATION_SECS: u64 = 3_600;
+/// Headroom added on top of the simulated gas used when estimating the max gas amount
+pub const GAS_ESTIMATE_MARGIN_PCT: u64 = 20;
+/// The chain's bound on the max gas amount of a transaction, the ceiling of a gas estimate
+pub const MAX_SIMULATION_GAS_AMOUNT: u64 = 2_000_000;
+/// How often a pending transaction is looked up while waiting for it
+pub const STATUS_POLL_INTERVAL_MILLIS: u64 = 500;
+/// How long an unknown hash is polled for, in case the transaction has not reached the node yet
+pub const STATUS_NOT_FOUND_TIMEOUT_SECS: u64 = 30;
+/// Number of transactions submitted per batch request
+pub const DEFAULT_BATCH_SIZE: usize = 20;
+/// Bytes of code and package metadata which fit in one transaction, leaving room for
+/// the rest of it under the 64KB limit
+pub const MAX_PUBLISH_PACKAGE_SIZE: usize = 60_000;
+/// Minimum raise of the gas unit price when replacing a pending transaction, so the
+/// mempool prefers the replacement
+pub const REPLACE_GAS_PRICE_BUMP_PCT: u64 = 20;
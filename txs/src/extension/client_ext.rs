@@ -1,23 +1,34 @@
-use crate::util::{format_args, format_type_args, parse_function_id};
-use anyhow::{anyhow, Context, Result};
+use crate::{
+    abi::{get_function_abi, split_args, typed_args},
+    constant::{GAS_ESTIMATE_MARGIN_PCT, MAX_SIMULATION_GAS_AMOUNT, REPLACE_GAS_PRICE_BUMP_PCT},
+    simulation::SimulationReport,
+    util::parse_function_id,
+    vm_status::TxFailure,
+};
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
+use std::path::Path;
 use std::time::SystemTime;
 use std::{str::FromStr, time::UNIX_EPOCH};
 use zapatos_sdk::{
+    crypto::{ed25519::Ed25519Signature, HashValue},
     move_types::{
+        identifier::Identifier,
         language_storage::{ModuleId, TypeTag},
-        parser::{parse_transaction_arguments, parse_type_tags},
-        transaction_argument::convert_txn_args,
+        parser::{parse_transaction_arguments, parse_type_tag, parse_type_tags},
     },
     rest_client::{
-        aptos_api_types::{EntryFunctionId, MoveType, ViewRequest},
+        aptos_api_types::{EntryFunctionId, MoveType, Transaction, TransactionData, ViewRequest},
         Account, Client,
     },
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
         chain_id::ChainId,
-        transaction::{EntryFunction, SignedTransaction, TransactionArgument, TransactionPayload},
+        transaction::{
+            EntryFunction, RawTransaction, Script, SignedTransaction, TransactionArgument,
+            TransactionPayload,
+        },
         LocalAccount,
     },
 };
@@ -26,15 +37,49 @@ use zapatos_sdk::{
 pub trait ClientExt {
     async fn get_sequence_number(&self, account: AccountAddress) -> Result<u64>;
 
-    async fn generate_transaction(
+    /// Build an EntryFunction payload, checking and encoding the args against the function's ABI
+    async fn entry_function_payload_ext(
         &self,
-        from_account: &mut LocalAccount,
         function_id: &str,
         ty_args: Option<String>,
         args: Option<String>,
+    ) -> Result<TransactionPayload>;
+
+    async fn sign_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        payload: TransactionPayload,
         options: TransactionOptions,
     ) -> Result<SignedTransaction>;
 
+    /// Dry run a payload on the node, without signing or submitting it
+    async fn simulate_payload(
+        &self,
+        from_account: &LocalAccount,
+        payload: TransactionPayload,
+        options: &TransactionOptions,
+    ) -> Result<SimulationReport>;
+
+    /// Simulate a payload to set the max gas amount to the gas used plus a margin,
+    /// and set the gas unit price to the node's estimate. Values given by the user are kept.
+    async fn estimate_gas(
+        &self,
+        from_account: &LocalAccount,
+        payload: TransactionPayload,
+        max_gas: Option<u64>,
+        gas_unit_price: Option<u64>,
+        options: TransactionOptions,
+    ) -> Result<TransactionOptions>;
+
+    /// Wait for an already submitted transaction, explaining the failure if it aborts
+    async fn wait_for_signed_transaction_ext(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Transaction>;
+
+    /// The signed transaction of a hash which is still in the mempool
+    async fn get_pending_transaction(&self, hash: HashValue) -> Result<SignedTransaction>;
+
     async fn view_ext(
         &self,
         function_id: &str,
@@ -57,53 +102,160 @@ impl ClientExt for Client {
         }
     }
 
-    async fn generate_transaction(
+    async fn entry_function_payload_ext(
         &self,
-        from_account: &mut LocalAccount,
         function_id: &str,
         ty_args: Option<String>,
         args: Option<String>,
+    ) -> Result<TransactionPayload> {
+        let (module_address, module_name, function_name) = parse_function_id(function_id)?;
+        let ty_args = parse_ty_args(ty_args.as_deref())?;
+        let function = get_function_abi(
+            self,
+            module_address,
+            module_name.as_str(),
+            function_name.as_str(),
+        )
+        .await?;
+        if !function.is_entry {
+            bail!("{function_id} is not an entry function");
+        }
+        let args = typed_args(&function, &ty_args, &split_args(args.as_deref())?)?;
+
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(module_address, module_name),
+            function_name,
+            ty_args,
+            args.iter().map(|arg| arg.to_bcs()).collect::<Result<_>>()?,
+        )))
+    }
+
+    async fn sign_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        payload: TransactionPayload,
         options: TransactionOptions,
     ) -> Result<SignedTransaction> {
         let chain_id = self.get_index().await?.inner().chain_id;
-        let (module_address, module_name, function_name) = parse_function_id(function_id)?;
-        let module = ModuleId::new(module_address, module_name);
-        let ty_args: Vec<TypeTag> = if let Some(ty_args) = ty_args {
-            parse_type_tags(&ty_args)
-                .context(format!("Unable to parse the type argument(s): {ty_args}"))?
-        } else {
-            vec![]
+        let transaction_builder = transaction_builder(payload, chain_id, &options);
+        Ok(from_account.sign_with_transaction_builder(transaction_builder))
+    }
+
+    async fn simulate_payload(
+        &self,
+        from_account: &LocalAccount,
+        payload: TransactionPayload,
+        options: &TransactionOptions,
+    ) -> Result<SimulationReport> {
+        let chain_id = self.get_index().await?.inner().chain_id;
+        let raw_trans: RawTransaction = transaction_builder(payload, chain_id, options)
+            .sender(from_account.address())
+            .sequence_number(from_account.sequence_number())
+            .build();
+        // The simulate endpoint rejects transactions with a valid signature
+        let signature = Ed25519Signature::try_from([0u8; 64].as_ref())?;
+        let signed_trans =
+            SignedTransaction::new(raw_trans, from_account.public_key().to_owned(), signature);
+
+        let user_trans = self
+            .simulate(&signed_trans)
+            .await
+            .context("Failed to simulate the transaction")?
+            .into_inner()
+            .into_iter()
+            .next()
+            .context("Simulation returned no transaction")?;
+        SimulationReport::new(self, &user_trans).await
+    }
+
+    async fn estimate_gas(
+        &self,
+        from_account: &LocalAccount,
+        payload: TransactionPayload,
+        max_gas: Option<u64>,
+        gas_unit_price: Option<u64>,
+        options: TransactionOptions,
+    ) -> Result<TransactionOptions> {
+        let gas_unit_price = match gas_unit_price {
+            Some(price) => price,
+            None => {
+                self.estimate_gas_price()
+                    .await
+                    .context("Failed to get the gas price estimate")?
+                    .into_inner()
+                    .gas_estimate
+            }
         };
-        let args: Vec<TransactionArgument> = if let Some(args) = args {
-            parse_transaction_arguments(&args)
-                .context(format!("Unable to parse argument(s): {args}"))?
-        } else {
-            vec![]
+        let max_gas_amount = match max_gas {
+            Some(max_gas) => max_gas,
+            None => {
+                // simulated with as much gas as the account can pay for, so a heavy
+                // payload is measured instead of running out of the default max gas
+                let balance = self
+                    .get_account_balance(from_account.address())
+                    .await
+                    .context("Failed to get the balance of the signing account")?
+                    .into_inner()
+                    .get();
+                let ceiling = TransactionOptions {
+                    max_gas_amount: (balance / gas_unit_price.max(1))
+                        .min(MAX_SIMULATION_GAS_AMOUNT),
+                    gas_unit_price,
+                    ..options
+                };
+                let report = self
+                    .simulate_payload(from_account, payload, &ceiling)
+                    .await?;
+                if !report.success {
+                    return Err(anyhow!(
+                        "Simulation failed, unable to estimate gas: {}",
+                        report.vm_status
+                    ));
+                }
+                (report.gas_used * (100 + GAS_ESTIMATE_MARGIN_PCT) / 100)
+                    .min(MAX_SIMULATION_GAS_AMOUNT)
+            }
         };
+        Ok(TransactionOptions {
+            max_gas_amount,
+            gas_unit_price,
+            ..options
+        })
+    }
 
-        println!("{}", format_type_args(&ty_args));
-        println!("{}", format_args(&args));
-
-        let expiration_timestamp_secs = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + options.timeout_secs;
-
-        let transaction_builder = TransactionBuilder::new(
-            TransactionPayload::EntryFunction(EntryFunction::new(
-                module,
-                function_name,
-                ty_args,
-                convert_txn_args(&args),
-            )),
-            expiration_timestamp_secs,
-            ChainId::new(chain_id),
-        )
-        .max_gas_amount(options.max_gas_amount)
-        .gas_unit_price(options.gas_unit_price);
+    async fn wait_for_signed_transaction_ext(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Transaction> {
+        match self.wait_for_signed_transaction(signed_trans).await {
+            Ok(res) => Ok(res.into_inner()),
+            Err(err) => {
+                // an aborted transaction is still committed, look it up to explain why
+                let hash = signed_trans.clone().committed_hash();
+                match self
+                    .get_transaction_by_hash(hash)
+                    .await
+                    .map(|res| res.into_inner())
+                {
+                    Ok(Transaction::UserTransaction(user_trans)) if !user_trans.info.success => {
+                        Err(TxFailure::new(&user_trans).into())
+                    }
+                    _ => Err(err.into()),
+                }
+            }
+        }
+    }
 
-        Ok(from_account.sign_with_transaction_builder(transaction_builder))
+    async fn get_pending_transaction(&self, hash: HashValue) -> Result<SignedTransaction> {
+        let data = self
+            .get_transaction_by_hash_bcs(hash)
+            .await
+            .context(format!("Failed to get the transaction {hash}"))?
+            .into_inner();
+        match data {
+            TransactionData::Pending(signed_trans) => Ok(*signed_trans),
+            TransactionData::OnChain(_) => bail!("Transaction {hash} is already committed"),
+        }
     }
 
     async fn view_ext(
@@ -114,29 +266,21 @@ impl ClientExt for Client {
     ) -> Result<Vec<serde_json::Value>> {
         let entry_fuction_id = EntryFunctionId::from_str(function_id)
             .context(format!("Invalid function id: {function_id}"))?;
-        let ty_args: Vec<MoveType> = if let Some(ty_args) = ty_args {
-            parse_type_tags(&ty_args)
-                .context(format!("Unable to parse the type argument(s): {ty_args}"))?
+        let (module_address, module_name, function_name) = parse_function_id(function_id)?;
+        let type_tags = parse_ty_args(ty_args.as_deref())?;
+        let function = get_function_abi(
+            self,
+            module_address,
+            module_name.as_str(),
+            function_name.as_str(),
+        )
+        .await?;
+        let args: Vec<serde_json::Value> =
+            typed_args(&function, &type_tags, &split_args(args.as_deref())?)?
                 .iter()
-                .map(|t| t.into())
-                .collect()
-        } else {
-            vec![]
-        };
-        let args: Vec<serde_json::Value> = if let Some(args) = args {
-            let mut output = vec![];
-            for arg in args.split(',') {
-                let arg = serde_json::Value::try_from(arg.trim())
-                    .context(format!("Failed to parse argument: {arg}"))?;
-                output.push(arg);
-            }
-            output
-        } else {
-            vec![]
-        };
-
-        println!("{}", format_type_args(&ty_args));
-        println!("{}", format_args(&args));
+                .map(|arg| arg.to_json())
+                .collect();
+        let ty_args: Vec<MoveType> = type_tags.iter().map(|t| t.into()).collect();
 
         let request = ViewRequest {
             function: entry_fuction_id,
@@ -151,8 +295,87 @@ impl ClientExt for Client {
     }
 }
 
+/// Build a Script payload from a compiled `.mv` script file and comma separated type args and args
+pub fn script_payload(
+    script_file: &Path,
+    ty_args: Option<String>,
+    args: Option<String>,
+) -> Result<TransactionPayload> {
+    let code =
+        std::fs::read(script_file).context(format!("Failed to read {}", script_file.display()))?;
+    let ty_args = parse_ty_args(ty_args.as_deref())?;
+    let args: Vec<TransactionArgument> = match args {
+        Some(args) => parse_transaction_arguments(&args)
+            .context(format!("Unable to parse argument(s): {args}"))?,
+        None => vec![],
+    };
+
+    Ok(TransactionPayload::Script(Script::new(code, ty_args, args)))
+}
+
+/// `0x1::coin::transfer<AptosCoin>`, the same call the CoinClient makes
+pub fn transfer_payload(to_account: AccountAddress, amount: u64) -> Result<TransactionPayload> {
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("coin")?),
+        Identifier::new("transfer")?,
+        vec![parse_type_tag("0x1::aptos_coin::AptosCoin")?],
+        vec![bcs::to_bytes(&to_account)?, bcs::to_bytes(&amount)?],
+    )))
+}
+
+fn parse_ty_args(ty_args: Option<&str>) -> Result<Vec<TypeTag>> {
+    match ty_args {
+        Some(ty_args) => parse_type_tags(ty_args)
+            .context(format!("Unable to parse the type argument(s): {ty_args}")),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn transaction_builder(
+    payload: TransactionPayload,
+    chain_id: u8,
+    options: &TransactionOptions,
+) -> TransactionBuilder {
+    let expiration_timestamp_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + options.expiration_secs;
+
+    TransactionBuilder::new(payload, expiration_timestamp_secs, ChainId::new(chain_id))
+        .max_gas_amount(options.max_gas_amount)
+        .gas_unit_price(options.gas_unit_price)
+}
+
+/// The gas unit price of a transaction replacing a pending one at the same sequence
+/// number, which has to be higher for the mempool to accept it
+pub fn replacement_gas_unit_price(pending: u64, requested: Option<u64>) -> Result<u64> {
+    match requested {
+        Some(price) if price <= pending => {
+            bail!("The gas unit price has to be higher than the pending {pending}")
+        }
+        Some(price) => Ok(price),
+        None => Ok((pending * (100 + REPLACE_GAS_PRICE_BUMP_PCT) / 100).max(pending + 1)),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct TransactionOptions {
     pub max_gas_amount: u64,
     pub gas_unit_price: u64,
-    pub timeout_secs: u64,
+    /// seconds from signing until the transaction expires
+    pub expiration_secs: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raises_replacement_gas_price() {
+        assert_eq!(replacement_gas_unit_price(100, None).unwrap(), 120);
+        assert_eq!(replacement_gas_unit_price(1, None).unwrap(), 2);
+        assert_eq!(replacement_gas_unit_price(100, Some(150)).unwrap(), 150);
+        assert!(replacement_gas_unit_price(100, Some(100)).is_err());
+    }
 }
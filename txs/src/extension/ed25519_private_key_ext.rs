@@ -5,19 +5,36 @@ use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
 use zapatos_sdk::{
     crypto::ed25519::Ed25519PrivateKey,
     rest_client::Client,
-    types::{AccountKey, LocalAccount},
+    types::{account_address::AccountAddress, AccountKey, LocalAccount},
 };
 
 #[async_trait]
 pub trait Ed25519PrivateKeyExt {
     async fn get_account(&self, sequence_number: Option<u64>) -> Result<LocalAccount>;
+
+    /// Same as `get_account`, but for an account whose address is not derived from this key,
+    /// e.g. after a key rotation
+    async fn get_account_at(
+        &self,
+        account_address: Option<AccountAddress>,
+        sequence_number: Option<u64>,
+    ) -> Result<LocalAccount>;
 }
 
 #[async_trait]
 impl Ed25519PrivateKeyExt for Ed25519PrivateKey {
     async fn get_account(&self, sequence_number: Option<u64>) -> Result<LocalAccount> {
+        self.get_account_at(None, sequence_number).await
+    }
+
+    async fn get_account_at(
+        &self,
+        account_address: Option<AccountAddress>,
+        sequence_number: Option<u64>,
+    ) -> Result<LocalAccount> {
         let account_key = AccountKey::from_private_key(self.to_owned());
-        let account_address = account_key.authentication_key().derived_address();
+        let account_address =
+            account_address.unwrap_or_else(|| account_key.authentication_key().derived_address());
         let sequence_number = match sequence_number {
             Some(seq) => seq,
             None => {
@@ -0,0 +1,207 @@
+//! on chain governance with `0x1::aptos_governance`, where stake pools create and vote on
+//! proposals through the voter named in owner.yaml
+
+use crate::validator::read_yaml;
+use anyhow::{bail, Context, Result};
+use libra_wallet::validator_files::OWNER_FILE;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use zapatos_genesis::config::OwnerConfiguration;
+use zapatos_sdk::{
+    crypto::HashValue,
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::{aptos_api_types::ViewRequest, Client},
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, Script, TransactionArgument, TransactionPayload},
+    },
+};
+
+pub const GOVERNANCE_PROPOSAL: &str = "0x1::governance_proposal::GovernanceProposal";
+pub const VOTING_FORUM: &str =
+    "0x1::voting::VotingForum<0x1::governance_proposal::GovernanceProposal>";
+
+/// A stake pool and the account allowed to vote with it
+pub struct Voter {
+    pub pool_address: AccountAddress,
+    pub voter_address: AccountAddress,
+}
+
+impl Voter {
+    pub fn read(home_dir: &Path) -> Result<Self> {
+        let owner: OwnerConfiguration = read_yaml(&home_dir.join(OWNER_FILE))?;
+        Ok(Self {
+            pool_address: owner.owner_account_address.into(),
+            voter_address: owner.voter_account_address.into(),
+        })
+    }
+
+    /// Only the voter of the pool can create proposals and vote with its stake
+    pub fn check_signer(&self, signer: AccountAddress) -> Result<()> {
+        if signer != self.voter_address {
+            bail!(
+                "The voter of pool {} is {}, not the signing account {}",
+                self.pool_address.to_hex_literal(),
+                self.voter_address.to_hex_literal(),
+                signer.to_hex_literal()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `0x1::aptos_governance::create_proposal`, for the script whose sha3-256 hash is
+/// `execution_hash`
+pub fn create_proposal_payload(
+    pool_address: AccountAddress,
+    execution_hash: HashValue,
+    metadata_url: &str,
+    metadata_hash: &[u8],
+) -> Result<TransactionPayload> {
+    governance_payload(
+        "create_proposal",
+        vec![
+            bcs::to_bytes(&pool_address)?,
+            bcs::to_bytes(&execution_hash.to_vec())?,
+            bcs::to_bytes(&metadata_url.as_bytes().to_vec())?,
+            bcs::to_bytes(&metadata_hash.to_vec())?,
+        ],
+    )
+}
+
+/// `0x1::aptos_governance::vote`, with all the voting power of the pool
+pub fn vote_payload(
+    pool_address: AccountAddress,
+    proposal_id: u64,
+    should_pass: bool,
+) -> Result<TransactionPayload> {
+    governance_payload(
+        "vote",
+        vec![
+            bcs::to_bytes(&pool_address)?,
+            bcs::to_bytes(&proposal_id)?,
+            bcs::to_bytes(&should_pass)?,
+        ],
+    )
+}
+
+/// An approved proposal is resolved by running its script, which takes the proposal id
+pub fn resolve_payload(script_code: Vec<u8>, proposal_id: u64) -> TransactionPayload {
+    TransactionPayload::Script(Script::new(
+        script_code,
+        vec![],
+        vec![TransactionArgument::U64(proposal_id)],
+    ))
+}
+
+/// The execution hash a proposal records for its script
+pub fn script_hash(script_code: &[u8]) -> HashValue {
+    HashValue::sha3_256_of(script_code)
+}
+
+fn governance_payload(function: &str, args: Vec<Vec<u8>>) -> Result<TransactionPayload> {
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("aptos_governance")?),
+        Identifier::new(function)?,
+        vec![],
+        args,
+    )))
+}
+
+/// A proposal with its tally, from the `0x1::voting` view functions
+#[derive(Debug, Serialize)]
+pub struct ProposalView {
+    pub id: u64,
+    pub state: String,
+    pub yes_votes: u128,
+    pub no_votes: u128,
+    pub expiration_secs: u64,
+    pub execution_hash: String,
+    pub is_resolved: bool,
+}
+
+impl ProposalView {
+    /// Open for voting, or approved and waiting to be resolved
+    pub fn is_active(&self) -> bool {
+        self.state == "pending" || (self.state == "succeeded" && !self.is_resolved)
+    }
+}
+
+pub async fn get_proposal(client: &Client, proposal_id: u64) -> Result<ProposalView> {
+    let state = voting_view(client, "get_proposal_state", proposal_id).await?;
+    let votes = voting_view(client, "get_votes", proposal_id).await?;
+    let expiration = voting_view(client, "get_proposal_expiration_secs", proposal_id).await?;
+    let execution_hash = voting_view(client, "get_execution_hash", proposal_id).await?;
+    let is_resolved = voting_view(client, "is_resolved", proposal_id).await?;
+
+    Ok(ProposalView {
+        id: proposal_id,
+        state: match parse_number::<u64>(state.first())? {
+            0 => "pending",
+            1 => "succeeded",
+            3 => "failed",
+            _ => "unknown",
+        }
+        .to_owned(),
+        yes_votes: parse_number(votes.first())?,
+        no_votes: parse_number(votes.get(1))?,
+        expiration_secs: parse_number(expiration.first())?,
+        execution_hash: execution_hash
+            .first()
+            .and_then(Value::as_str)
+            .context("Unexpected execution hash")?
+            .to_owned(),
+        is_resolved: is_resolved
+            .first()
+            .and_then(Value::as_bool)
+            .context("Unexpected resolution")?,
+    })
+}
+
+/// The active proposals, or every proposal with `all`
+pub async fn list_proposals(client: &Client, all: bool) -> Result<Vec<ProposalView>> {
+    let forum = client
+        .get_account_resource(AccountAddress::ONE, VOTING_FORUM)
+        .await
+        .context("Failed to get the governance voting forum")?
+        .into_inner()
+        .context("On chain governance is not initialized")?;
+    let next_proposal_id = parse_number(forum.data.get("next_proposal_id"))?;
+
+    let mut proposals = vec![];
+    for id in 0..next_proposal_id {
+        let proposal = get_proposal(client, id).await?;
+        if all || proposal.is_active() {
+            proposals.push(proposal);
+        }
+    }
+    Ok(proposals)
+}
+
+async fn voting_view(client: &Client, function: &str, proposal_id: u64) -> Result<Vec<Value>> {
+    let request = ViewRequest {
+        function: format!("0x1::voting::{function}").parse()?,
+        type_arguments: vec![GOVERNANCE_PROPOSAL.parse()?],
+        arguments: vec![
+            Value::String(AccountAddress::ONE.to_hex_literal()),
+            Value::String(proposal_id.to_string()),
+        ],
+    };
+    Ok(client
+        .view(&request, None)
+        .await
+        .context(format!("Failed to read proposal {proposal_id}"))?
+        .into_inner())
+}
+
+/// Integers of 64 bits and more are json strings
+fn parse_number<T: std::str::FromStr>(value: Option<&Value>) -> Result<T> {
+    value
+        .and_then(|v| match v {
+            Value::String(s) => s.parse().ok(),
+            Value::Number(n) => n.to_string().parse().ok(),
+            _ => None,
+        })
+        .context("Unexpected number in the view response")
+}
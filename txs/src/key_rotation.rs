@@ -0,0 +1,102 @@
+//! rotate the authentication key of an account to a new ed25519 key
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use zapatos::account::key_rotation::lookup_address;
+use zapatos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        ValidCryptoMaterial,
+    },
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::Client,
+    types::{
+        account_address::AccountAddress,
+        transaction::{authenticator::AuthenticationKey, EntryFunction, TransactionPayload},
+    },
+};
+
+const ED25519_SCHEME: u8 = 0;
+
+/// BCS layout of `0x1::account::RotationProofChallenge`, which both the current and the
+/// new key sign to prove the owner holds them
+#[derive(Serialize)]
+struct RotationProofChallenge {
+    account_address: AccountAddress,
+    module_name: String,
+    struct_name: String,
+    sequence_number: u64,
+    originator: AccountAddress,
+    current_auth_key: AccountAddress,
+    new_public_key: Vec<u8>,
+}
+
+/// Build the `0x1::account::rotate_authentication_key` call for an account. The proof is
+/// signed for `sequence_number`, which has to be the one the transaction is sent at
+pub async fn rotate_key_payload(
+    client: &Client,
+    account_address: AccountAddress,
+    sequence_number: u64,
+    current_key: &Ed25519PrivateKey,
+    new_key: &Ed25519PrivateKey,
+) -> Result<TransactionPayload> {
+    let account = client
+        .get_account(account_address)
+        .await
+        .context(format!(
+            "Failed to get the account {}",
+            account_address.to_hex_literal()
+        ))?
+        .into_inner();
+    let current_public_key = Ed25519PublicKey::from(current_key);
+    if AuthenticationKey::ed25519(&current_public_key) != account.authentication_key {
+        bail!("The signing key is not the current key of the account");
+    }
+    let new_public_key = Ed25519PublicKey::from(new_key);
+
+    let challenge = bcs::to_bytes(&RotationProofChallenge {
+        account_address: AccountAddress::ONE,
+        module_name: "account".to_owned(),
+        struct_name: "RotationProofChallenge".to_owned(),
+        sequence_number,
+        originator: account_address,
+        current_auth_key: account.authentication_key.derived_address(),
+        new_public_key: new_public_key.to_bytes().to_vec(),
+    })?;
+    let signed_by_current_key = current_key.sign_arbitrary_message(&challenge);
+    let signed_by_new_key = new_key.sign_arbitrary_message(&challenge);
+
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("account")?),
+        Identifier::new("rotate_authentication_key")?,
+        vec![],
+        vec![
+            bcs::to_bytes(&ED25519_SCHEME)?,
+            bcs::to_bytes(&current_public_key.to_bytes().to_vec())?,
+            bcs::to_bytes(&ED25519_SCHEME)?,
+            bcs::to_bytes(&new_public_key.to_bytes().to_vec())?,
+            bcs::to_bytes(&signed_by_current_key.to_bytes().to_vec())?,
+            bcs::to_bytes(&signed_by_new_key.to_bytes().to_vec())?,
+        ],
+    )))
+}
+
+/// Check that `0x1::account::OriginatingAddress` maps the new key to the account
+pub async fn verify_rotation(
+    client: &Client,
+    account_address: AccountAddress,
+    new_public_key: &Ed25519PublicKey,
+) -> Result<()> {
+    let new_auth_key = AuthenticationKey::ed25519(new_public_key).derived_address();
+    let originating_address = lookup_address(client, new_auth_key, true)
+        .await
+        .context("Failed to look up the new key in OriginatingAddress")?;
+    if originating_address != account_address {
+        bail!(
+            "The new key maps to {} instead of {}",
+            originating_address.to_hex_literal(),
+            account_address.to_hex_literal()
+        );
+    }
+    Ok(())
+}
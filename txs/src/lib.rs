@@ -1,6 +1,17 @@
+pub mod abi;
+pub mod batch;
 pub mod constant;
 pub mod extension;
+pub mod governance;
+pub mod key_rotation;
+pub mod multisig;
+pub mod publish;
+pub mod signer;
+pub mod simulation;
+pub mod status;
 pub mod util;
+pub mod validator;
+pub mod vm_status;
 pub mod coin_client {
     pub use zapatos_sdk::coin_client::*;
 }
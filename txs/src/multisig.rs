@@ -0,0 +1,320 @@
+//! k-of-n MultiEd25519 accounts and multi-agent transactions, signed by each key holder
+//! separately and combined into one signed transaction
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use zapatos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        Signature, SigningKey, ValidCryptoMaterialStringExt,
+    },
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::{AccountAuthenticator, AuthenticationKey},
+            RawTransaction, RawTransactionWithData, SignedTransaction,
+        },
+    },
+};
+
+/// The public keys and threshold of a k-of-n account
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultisigConfig {
+    pub threshold: u8,
+    pub public_keys: Vec<Ed25519PublicKey>,
+}
+
+impl MultisigConfig {
+    pub fn new(public_keys: &str, threshold: u8) -> Result<Self> {
+        let public_keys = public_keys
+            .split(',')
+            .map(|key| {
+                Ed25519PublicKey::from_encoded_string(key.trim())
+                    .context(format!("Failed to parse the public key {key}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let config = Self {
+            threshold,
+            public_keys,
+        };
+        // checks the threshold against the number of keys
+        config.public_key()?;
+        Ok(config)
+    }
+
+    pub fn public_key(&self) -> Result<MultiEd25519PublicKey> {
+        MultiEd25519PublicKey::new(self.public_keys.clone(), self.threshold).context(format!(
+            "Invalid {}-of-{} multisig",
+            self.threshold,
+            self.public_keys.len()
+        ))
+    }
+
+    pub fn authentication_key(&self) -> Result<AuthenticationKey> {
+        Ok(AuthenticationKey::multi_ed25519(&self.public_key()?))
+    }
+
+    /// Address of a new account created with this multisig authentication key
+    pub fn address(&self) -> Result<AccountAddress> {
+        Ok(self.authentication_key()?.derived_address())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str(&contents).context(format!("Invalid multisig file {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_yaml::to_string(self)?)
+            .context(format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The signature of one key holder, for the sender or a secondary signer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+}
+
+/// What every signer signs: the raw transaction, or with secondary signers the
+/// multi-agent form of it
+pub struct SigningMessage {
+    raw_trans: RawTransaction,
+    secondary_signers: Vec<AccountAddress>,
+}
+
+impl SigningMessage {
+    pub fn new(raw_trans: RawTransaction, secondary_signers: Vec<AccountAddress>) -> Self {
+        Self {
+            raw_trans,
+            secondary_signers,
+        }
+    }
+
+    fn multi_agent(&self) -> Option<RawTransactionWithData> {
+        if self.secondary_signers.is_empty() {
+            None
+        } else {
+            Some(RawTransactionWithData::new_multi_agent(
+                self.raw_trans.clone(),
+                self.secondary_signers.clone(),
+            ))
+        }
+    }
+
+    pub fn sign(&self, private_key: &Ed25519PrivateKey) -> Result<PartialSignature> {
+        let signature = match self.multi_agent() {
+            Some(message) => private_key.sign(&message)?,
+            None => private_key.sign(&self.raw_trans)?,
+        };
+        Ok(PartialSignature {
+            public_key: Ed25519PublicKey::from(private_key),
+            signature,
+        })
+    }
+
+    fn verify(&self, partial: &PartialSignature) -> Result<()> {
+        match self.multi_agent() {
+            Some(message) => partial.signature.verify(&message, &partial.public_key),
+            None => partial
+                .signature
+                .verify(&self.raw_trans, &partial.public_key),
+        }
+        .context(format!(
+            "Invalid signature from {}",
+            partial.public_key.to_encoded_string()?
+        ))
+    }
+}
+
+/// How the sender authorizes the transaction
+pub enum SenderSignatures {
+    Single(PartialSignature),
+    Multisig(MultisigConfig, Vec<PartialSignature>),
+}
+
+/// Combine the signatures of the sender and of each secondary signer, in the order of
+/// the secondary signer addresses, into a transaction ready to submit. Also returns a
+/// warning for each secondary signer whose key does not derive its address.
+pub fn combine_signatures(
+    message: SigningMessage,
+    sender: SenderSignatures,
+    secondary_signatures: Vec<PartialSignature>,
+) -> Result<(SignedTransaction, Vec<String>)> {
+    if secondary_signatures.len() != message.secondary_signers.len() {
+        bail!(
+            "Expected {} secondary signatures, got {}",
+            message.secondary_signers.len(),
+            secondary_signatures.len()
+        );
+    }
+    let sender = sender_authenticator(&message, sender)?;
+
+    if message.secondary_signers.is_empty() {
+        let signed_trans = match sender {
+            AccountAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => SignedTransaction::new(message.raw_trans, public_key, signature),
+            AccountAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => SignedTransaction::new_multisig(message.raw_trans, public_key, signature),
+        };
+        return Ok((signed_trans, vec![]));
+    }
+
+    let mut secondary_signers = vec![];
+    let mut warnings = vec![];
+    for (address, partial) in message.secondary_signers.iter().zip(secondary_signatures) {
+        message.verify(&partial)?;
+        if AuthenticationKey::ed25519(&partial.public_key).derived_address() != *address {
+            warnings.push(format!(
+                "The key of secondary signer {} does not derive its address, this is only expected if its key has been rotated",
+                address.to_hex_literal()
+            ));
+        }
+        secondary_signers.push(AccountAuthenticator::ed25519(
+            partial.public_key,
+            partial.signature,
+        ));
+    }
+    let signed_trans = SignedTransaction::new_multi_agent(
+        message.raw_trans,
+        sender,
+        message.secondary_signers,
+        secondary_signers,
+    );
+    Ok((signed_trans, warnings))
+}
+
+fn sender_authenticator(
+    message: &SigningMessage,
+    sender: SenderSignatures,
+) -> Result<AccountAuthenticator> {
+    match sender {
+        SenderSignatures::Single(partial) => {
+            message.verify(&partial)?;
+            Ok(AccountAuthenticator::ed25519(
+                partial.public_key,
+                partial.signature,
+            ))
+        }
+        SenderSignatures::Multisig(config, partials) => {
+            let public_key = config.public_key()?;
+            let mut signatures = vec![];
+            for partial in partials {
+                message.verify(&partial)?;
+                let index = config
+                    .public_keys
+                    .iter()
+                    .position(|key| *key == partial.public_key)
+                    .context(format!(
+                        "{} is not one of the multisig keys",
+                        partial.public_key.to_encoded_string()?
+                    ))? as u8;
+                if signatures.iter().any(|(_, i)| *i == index) {
+                    bail!("Two signatures from the same key");
+                }
+                signatures.push((partial.signature, index));
+            }
+            if signatures.len() < config.threshold as usize {
+                bail!(
+                    "Only {} of the {} required signatures",
+                    signatures.len(),
+                    config.threshold
+                );
+            }
+            let signature = MultiEd25519Signature::new(signatures)
+                .context("Failed to combine the signatures")?;
+            Ok(AccountAuthenticator::multi_ed25519(public_key, signature))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension::client_ext::transfer_payload;
+    use zapatos_sdk::{crypto::PrivateKey, types::chain_id::ChainId};
+
+    fn keys() -> Vec<Ed25519PrivateKey> {
+        (1..=3u8)
+            .map(|i| Ed25519PrivateKey::try_from([i; 32].as_ref()).unwrap())
+            .collect()
+    }
+
+    fn raw_transaction(sender: AccountAddress) -> RawTransaction {
+        RawTransaction::new(
+            sender,
+            0,
+            transfer_payload(AccountAddress::ONE, 1).unwrap(),
+            5_000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        )
+    }
+
+    #[test]
+    fn combines_two_of_three() {
+        let keys = keys();
+        let public_keys = keys
+            .iter()
+            .map(|k| k.public_key().to_encoded_string().unwrap())
+            .collect::<Vec<_>>()
+            .join(",");
+        let config = MultisigConfig::new(&public_keys, 2).unwrap();
+        let message = SigningMessage::new(raw_transaction(config.address().unwrap()), vec![]);
+
+        let one = vec![message.sign(&keys[2]).unwrap()];
+        assert!(sender_authenticator(&message, SenderSignatures::Multisig(config, one)).is_err());
+
+        let config = MultisigConfig::new(&public_keys, 2).unwrap();
+        let two = vec![
+            message.sign(&keys[2]).unwrap(),
+            message.sign(&keys[0]).unwrap(),
+        ];
+        let (signed, _) =
+            combine_signatures(message, SenderSignatures::Multisig(config, two), vec![]).unwrap();
+        assert!(signed.check_signature().is_ok());
+    }
+
+    #[test]
+    fn combines_multi_agent() {
+        let keys = keys();
+        let sender = AuthenticationKey::ed25519(&keys[0].public_key()).derived_address();
+        let secondary = AuthenticationKey::ed25519(&keys[1].public_key()).derived_address();
+        let message = SigningMessage::new(raw_transaction(sender), vec![secondary]);
+
+        let (signed, warnings) = combine_signatures(
+            SigningMessage::new(raw_transaction(sender), vec![secondary]),
+            SenderSignatures::Single(message.sign(&keys[0]).unwrap()),
+            vec![message.sign(&keys[1]).unwrap()],
+        )
+        .unwrap();
+        assert!(signed.check_signature().is_ok());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn warns_on_rotated_secondary_signer() {
+        let keys = keys();
+        let sender = AuthenticationKey::ed25519(&keys[0].public_key()).derived_address();
+        // signs for an address its key does not derive, as after a rotation
+        let rotated = AccountAddress::TWO;
+        let message = SigningMessage::new(raw_transaction(sender), vec![rotated]);
+
+        let (_, warnings) = combine_signatures(
+            SigningMessage::new(raw_transaction(sender), vec![rotated]),
+            SenderSignatures::Single(message.sign(&keys[0]).unwrap()),
+            vec![message.sign(&keys[1]).unwrap()],
+        )
+        .unwrap();
+        assert_eq!(warnings.len(), 1);
+    }
+}
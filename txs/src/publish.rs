@@ -0,0 +1,181 @@
+//! publish compiled Move modules with `0x1::code::publish_package_txn`
+
+use crate::constant::MAX_PUBLISH_PACKAGE_SIZE;
+use anyhow::{bail, Context, Result};
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use zapatos_framework::natives::code::{
+    ModuleMetadata, MoveOption, PackageMetadata, UpgradePolicy,
+};
+use zapatos_sdk::{
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+
+pub const PACKAGE_METADATA_FILE: &str = "package-metadata.bcs";
+pub const BYTECODE_MODULES_DIR: &str = "bytecode_modules";
+
+pub struct Package {
+    pub metadata: Vec<u8>,
+    /// bytecode in dependency order, as the VM loads them
+    pub modules: Vec<Vec<u8>>,
+}
+
+impl Package {
+    /// Read a package compiled by the Move CLI, i.e. a `build/<PACKAGE>` directory
+    pub fn from_dir(package_dir: &Path) -> Result<Self> {
+        let metadata_path = package_dir.join(PACKAGE_METADATA_FILE);
+        let metadata = std::fs::read(&metadata_path)
+            .context(format!("Failed to read {}", metadata_path.display()))?;
+        let package: PackageMetadata = bcs::from_bytes(&metadata).context(format!(
+            "Invalid package metadata {}",
+            metadata_path.display()
+        ))?;
+
+        let modules_dir = package_dir.join(BYTECODE_MODULES_DIR);
+        let mut module_files = vec![];
+        for entry in std::fs::read_dir(&modules_dir)
+            .context(format!("Failed to read {}", modules_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "mv").unwrap_or(false) {
+                module_files.push(path);
+            }
+        }
+        if module_files.len() != package.modules.len() {
+            bail!(
+                "Package {} lists {} modules but {} has {}",
+                package.name,
+                package.modules.len(),
+                modules_dir.display(),
+                module_files.len()
+            );
+        }
+
+        Ok(Self {
+            metadata,
+            modules: dependency_order(read_modules(&module_files)?)?
+                .into_iter()
+                .map(|(_, bytes)| bytes)
+                .collect(),
+        })
+    }
+
+    /// Publish bare modules, with metadata holding only their names
+    pub fn from_modules(package_name: &str, module_files: &[PathBuf]) -> Result<Self> {
+        let modules = dependency_order(read_modules(module_files)?)?;
+        let metadata = PackageMetadata {
+            name: package_name.to_owned(),
+            upgrade_policy: UpgradePolicy::compat(),
+            upgrade_number: 0,
+            source_digest: String::new(),
+            manifest: vec![],
+            modules: modules
+                .iter()
+                .map(|(id, _)| ModuleMetadata {
+                    name: id.name().to_string(),
+                    source: vec![],
+                    source_map: vec![],
+                    extension: MoveOption::none(),
+                })
+                .collect(),
+            deps: vec![],
+            extension: MoveOption::none(),
+        };
+
+        Ok(Self {
+            metadata: bcs::to_bytes(&metadata)?,
+            modules: modules.into_iter().map(|(_, bytes)| bytes).collect(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.metadata.len() + self.modules.iter().map(|m| m.len()).sum::<usize>()
+    }
+
+    /// The modules have to be published from the account at their address, in a
+    /// single transaction under the size limit
+    pub fn check(&self, sender: AccountAddress) -> Result<()> {
+        for bytes in &self.modules {
+            let id = CompiledModule::deserialize(bytes)?.self_id();
+            if id.address() != &sender {
+                bail!(
+                    "Module {} can only be published by {}",
+                    id.name(),
+                    id.address().to_hex_literal()
+                );
+            }
+        }
+        if self.size() > MAX_PUBLISH_PACKAGE_SIZE {
+            bail!(
+                "The package is {} bytes, over the {MAX_PUBLISH_PACKAGE_SIZE} bytes which fit in a transaction. Split it into smaller packages",
+                self.size()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn payload(&self) -> Result<TransactionPayload> {
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, Identifier::new("code")?),
+            Identifier::new("publish_package_txn")?,
+            vec![],
+            vec![
+                bcs::to_bytes(&self.metadata)?,
+                bcs::to_bytes(&self.modules)?,
+            ],
+        )))
+    }
+}
+
+fn read_modules(module_files: &[PathBuf]) -> Result<BTreeMap<ModuleId, Vec<u8>>> {
+    let mut modules = BTreeMap::new();
+    for path in module_files {
+        let bytes = std::fs::read(path).context(format!("Failed to read {}", path.display()))?;
+        let id = CompiledModule::deserialize(&bytes)
+            .context(format!("Invalid module bytecode {}", path.display()))?
+            .self_id();
+        modules.insert(id, bytes);
+    }
+    Ok(modules)
+}
+
+/// Order the modules so each comes after the modules of the package it depends on
+fn dependency_order(modules: BTreeMap<ModuleId, Vec<u8>>) -> Result<Vec<(ModuleId, Vec<u8>)>> {
+    let mut deps = BTreeMap::new();
+    for (id, bytes) in &modules {
+        let module_deps: Vec<ModuleId> = CompiledModule::deserialize(bytes)?
+            .immediate_dependencies()
+            .into_iter()
+            .filter(|dep| modules.contains_key(dep))
+            .collect();
+        deps.insert(id.clone(), module_deps);
+    }
+
+    let mut ordered: Vec<ModuleId> = vec![];
+    while ordered.len() < modules.len() {
+        let ready = deps
+            .iter()
+            .find(|(id, module_deps)| {
+                !ordered.contains(id) && module_deps.iter().all(|dep| ordered.contains(dep))
+            })
+            .map(|(id, _)| id.clone())
+            .context("The modules have a cyclic dependency")?;
+        ordered.push(ready);
+    }
+
+    let mut modules = modules;
+    Ok(ordered
+        .into_iter()
+        .map(|id| {
+            let bytes = modules.remove(&id).unwrap_or_default();
+            (id, bytes)
+        })
+        .collect())
+}
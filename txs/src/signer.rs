@@ -0,0 +1,128 @@
+//! resolve the key which signs transactions without passing it on the command line
+
+use crate::extension::ed25519_private_key_ext::Ed25519PrivateKeyExt;
+use anyhow::{anyhow, Context, Result};
+use dialoguer::Password;
+use libra_config::extension::{
+    cli_config_ext::CliConfigExt, client_ext::ClientExt as ConfigClientExt,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use zapatos::{
+    account::key_rotation::lookup_address,
+    common::types::{CliConfig, ConfigSearchMode},
+};
+use zapatos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        ValidCryptoMaterialStringExt,
+    },
+    rest_client::Client,
+    types::{
+        account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+        LocalAccount,
+    },
+};
+
+/// Where the private key of the signing account is loaded from
+#[derive(Clone, Debug)]
+pub enum SignerSource {
+    /// Private key of a profile in the libra-config config.yaml, `None` for the default profile
+    Profile(Option<String>),
+    /// private-keys.yaml written by libra-wallet keygen
+    KeyFile(PathBuf),
+    /// Environment variable holding the hex encoded private key
+    Env(String),
+    /// Hidden interactive prompt
+    Prompt,
+}
+
+/// The field of libra-wallet's private-keys.yaml needed to sign transactions. Its
+/// `account_address` is the one derived when the key was generated, which is not the
+/// account of a key rotated into another account, so it is not trusted.
+#[derive(Deserialize)]
+struct KeyFile {
+    account_private_key: Ed25519PrivateKey,
+}
+
+impl SignerSource {
+    /// The default profile when there is a config.yaml, otherwise the prompt
+    pub fn fallback() -> Self {
+        if CliConfig::config_exists_ext(ConfigSearchMode::CurrentDirAndParents) {
+            SignerSource::Profile(None)
+        } else {
+            SignerSource::Prompt
+        }
+    }
+
+    /// Load the private key, and the account address when the source knows it, which only
+    /// a profile does. The address is needed for accounts whose key has been rotated.
+    pub fn load(&self) -> Result<(Ed25519PrivateKey, Option<AccountAddress>)> {
+        match self {
+            SignerSource::Profile(profile) => {
+                let profile_name = profile.as_deref();
+                let profile = CliConfig::load_profile_ext(
+                    profile_name,
+                    ConfigSearchMode::CurrentDirAndParents,
+                )?
+                .context("Profile not found, have you run `libra-config init`?")?;
+                let private_key = profile.private_key.ok_or_else(|| {
+                    anyhow!(
+                        "Profile {} has no private key",
+                        profile_name.unwrap_or("default")
+                    )
+                })?;
+                Ok((private_key, profile.account))
+            }
+            SignerSource::KeyFile(path) => {
+                let bytes = std::fs::read(path)
+                    .context(format!("Failed to read key file {}", path.display()))?;
+                let key_file: KeyFile = serde_yaml::from_slice(&bytes)
+                    .context(format!("Failed to parse key file {}", path.display()))?;
+                Ok((key_file.account_private_key, None))
+            }
+            SignerSource::Env(var) => {
+                let private_key =
+                    std::env::var(var).context(format!("Environment variable {var} is not set"))?;
+                Ok((parse_private_key(&private_key)?, None))
+            }
+            SignerSource::Prompt => {
+                let private_key = Password::new()
+                    .with_prompt("Private key of the signing account")
+                    .interact()?;
+                Ok((parse_private_key(&private_key)?, None))
+            }
+        }
+    }
+
+    /// Build the signing account, fetching its sequence number from chain if not given.
+    /// When the source does not know the address, it is looked up from the key.
+    pub async fn get_account(&self, sequence_number: Option<u64>) -> Result<LocalAccount> {
+        let (private_key, account_address) = self.load()?;
+        let account_address = match account_address {
+            Some(address) => address,
+            None => originating_address(&Client::default()?, &private_key).await?,
+        };
+        private_key
+            .get_account_at(Some(account_address), sequence_number)
+            .await
+    }
+}
+
+/// The account a key signs for, from `0x1::account::OriginatingAddress`, since a rotated
+/// key does not derive the address of its account
+pub async fn originating_address(
+    client: &Client,
+    private_key: &Ed25519PrivateKey,
+) -> Result<AccountAddress> {
+    let derived =
+        AuthenticationKey::ed25519(&Ed25519PublicKey::from(private_key)).derived_address();
+    lookup_address(client, derived, false)
+        .await
+        .context("Failed to look up the account of the signing key")
+}
+
+fn parse_private_key(private_key: &str) -> Result<Ed25519PrivateKey> {
+    Ed25519PrivateKey::from_encoded_string(private_key.trim())
+        .context("Failed to parse the private key")
+}
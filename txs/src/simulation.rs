@@ -0,0 +1,98 @@
+//! summary of a simulated transaction: outcome, gas, coin balance changes and events
+
+use anyhow::{Context, Result};
+use query::extension::client_ext::ClientExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+use zapatos_sdk::{
+    rest_client::{aptos_api_types::UserTransaction, Client},
+    types::account_address::AccountAddress,
+};
+
+#[derive(Debug, Serialize)]
+pub struct SimulationReport {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub gas_unit_price: u64,
+    pub balance_changes: Vec<BalanceChange>,
+    pub events: Vec<SimulatedEvent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceChange {
+    pub account: AccountAddress,
+    pub coin_store: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulatedEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: Value,
+}
+
+impl SimulationReport {
+    /// Compare the coin stores written by the simulation with the current on-chain state
+    pub async fn new(client: &Client, user_trans: &UserTransaction) -> Result<Self> {
+        let mut balance_changes = vec![];
+        for change in &user_trans.info.changes {
+            let change = serde_json::to_value(change)?;
+            let resource_type = match change.pointer("/data/type").and_then(|v| v.as_str()) {
+                Some(t) if t.starts_with("0x1::coin::CoinStore") => t.to_owned(),
+                _ => continue,
+            };
+            let account = change
+                .get("address")
+                .and_then(|v| v.as_str())
+                .context("Write set change has no address")?;
+            let account = AccountAddress::from_str(account)
+                .context(format!("Failed to parse the address {account}"))?;
+            let after = coin_value(&change["data"]["data"])?;
+            // a new coin store starts from zero
+            let before = match client
+                .get_resource_data_ext(account, &resource_type)
+                .await?
+            {
+                Some(data) => coin_value(&data)?,
+                None => 0,
+            };
+            balance_changes.push(BalanceChange {
+                account,
+                coin_store: resource_type,
+                before,
+                after,
+            });
+        }
+
+        let events = user_trans
+            .events
+            .iter()
+            .map(|event| SimulatedEvent {
+                event_type: event.typ.to_string(),
+                data: event.data.to_owned(),
+            })
+            .collect();
+
+        Ok(Self {
+            success: user_trans.info.success,
+            vm_status: user_trans.info.vm_status.to_owned(),
+            gas_used: user_trans.info.gas_used.0,
+            gas_unit_price: user_trans.request.gas_unit_price.0,
+            balance_changes,
+            events,
+        })
+    }
+}
+
+fn coin_value(coin_store: &Value) -> Result<u64> {
+    coin_store
+        .pointer("/coin/value")
+        .and_then(|v| v.as_str())
+        .context("CoinStore has no coin value")?
+        .parse()
+        .context("CoinStore value is not a u64")
+}
@@ -0,0 +1,127 @@
+//! follow a submitted transaction by its hash until it commits or expires
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode, Transaction},
+        error::{AptosErrorResponse, RestError},
+        Client,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxState {
+    Pending,
+    Committed,
+    /// was pending, but can no longer be committed
+    Expired,
+    /// unknown to the node, either never submitted or already dropped from its mempool
+    NotFound,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TxStatus {
+    pub hash: String,
+    pub state: TxState,
+    pub version: Option<u64>,
+    pub success: Option<bool>,
+    pub gas_used: Option<u64>,
+    pub vm_status: Option<String>,
+    pub expiration_timestamp_secs: Option<u64>,
+}
+
+impl TxStatus {
+    pub fn new(hash: HashValue, trans: &Transaction) -> Result<Self> {
+        let mut status = Self::not_found(hash);
+        match trans {
+            Transaction::PendingTransaction(pending) => {
+                status.state = TxState::Pending;
+                status.expiration_timestamp_secs =
+                    Some(pending.request.expiration_timestamp_secs.0);
+            }
+            committed => {
+                status.state = TxState::Committed;
+                status.version = committed.version();
+                status.success = Some(committed.success());
+                status.gas_used = Some(committed.transaction_info()?.gas_used.0);
+                status.vm_status = Some(committed.vm_status());
+            }
+        }
+        Ok(status)
+    }
+
+    fn not_found(hash: HashValue) -> Self {
+        Self {
+            hash: hash.to_hex_literal(),
+            state: TxState::NotFound,
+            version: None,
+            success: None,
+            gas_used: None,
+            vm_status: None,
+            expiration_timestamp_secs: None,
+        }
+    }
+}
+
+/// Accepts hashes with or without the `0x` prefix
+pub fn parse_hash(hash: &str) -> Result<HashValue> {
+    HashValue::from_hex_literal(hash)
+        .or_else(|_| HashValue::from_hex(hash))
+        .context(format!("Invalid transaction hash {hash}"))
+}
+
+pub async fn get_status(client: &Client, hash: HashValue) -> Result<TxStatus> {
+    match client.get_transaction_by_hash(hash).await {
+        Ok(res) => TxStatus::new(hash, &res.into_inner()),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::TransactionNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(TxStatus::not_found(hash)),
+        Err(err) => Err(err).context(format!("Failed to look up {}", hash.to_hex_literal())),
+    }
+}
+
+/// Poll a pending transaction until it is committed, or the ledger time has passed its
+/// expiration. A hash the node does not know is polled for up to `not_found_timeout`.
+pub async fn wait_for_status(
+    client: &Client,
+    hash: HashValue,
+    poll_interval: Duration,
+    not_found_timeout: Duration,
+) -> Result<TxStatus> {
+    let started = Instant::now();
+    let mut expiration = None;
+    loop {
+        let mut status = get_status(client, hash).await?;
+        match status.state {
+            TxState::Pending => expiration = status.expiration_timestamp_secs,
+            // dropped from the mempool after we saw it pending
+            TxState::NotFound if expiration.is_some() => {
+                status.state = TxState::Expired;
+                status.expiration_timestamp_secs = expiration;
+                return Ok(status);
+            }
+            // may not have reached this node yet
+            TxState::NotFound if started.elapsed() < not_found_timeout => {}
+            _ => return Ok(status),
+        }
+
+        if let Some(secs) = expiration {
+            // the chain decides expiry by its own clock, not the local one
+            let ledger_secs = client.get_index().await?.inner().ledger_timestamp.0 / 1_000_000;
+            if ledger_secs > secs {
+                status.state = TxState::Expired;
+                return Ok(status);
+            }
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
@@ -0,0 +1,62 @@
+use super::TransactionArgs;
+use anyhow::Result;
+use libra_config::extension::client_ext::ClientExt;
+use query::output::{format_output, OutputFormat};
+use std::path::{Path, PathBuf};
+use txs::{
+    batch::{
+        load_report, read_manifest, reconcile_submitted, submit_batch, BatchOptions, RowStatus,
+    },
+    rest_client::Client,
+    signer::SignerSource,
+};
+
+pub async fn run(
+    manifest_path: &Path,
+    report_path: Option<PathBuf>,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
+    options: &BatchOptions,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = Client::default()?;
+    let manifest = read_manifest(manifest_path)?;
+    let report_path = report_path.unwrap_or_else(|| manifest_path.with_extension("report.json"));
+    let mut report = load_report(&report_path, manifest.len())?;
+    reconcile_submitted(&client, &mut report).await?;
+
+    let done = report
+        .iter()
+        .filter(|r| r.status == RowStatus::Success)
+        .count();
+    eprintln!(
+        "{done} of {} rows already succeeded, report at {}",
+        manifest.len(),
+        report_path.display()
+    );
+    let failed = report
+        .iter()
+        .filter(|r| r.status == RowStatus::Failed)
+        .count();
+    if failed > 0 && !options.retry_failed {
+        eprintln!(
+            "Skipping {failed} rows which failed, rerun with --retry-failed to send them again"
+        );
+    }
+
+    // the sequence number is fetched once, then incremented locally for each row
+    let mut account = signer.get_account(transaction.sequence_number).await?;
+    let outcome = submit_batch(
+        &client,
+        &mut account,
+        &manifest,
+        &mut report,
+        &report_path,
+        options,
+    )
+    .await;
+
+    // the report is shown even when the run stops part way
+    println!("{}", format_output(&report, output)?);
+    outcome
+}
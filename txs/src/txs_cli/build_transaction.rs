@@ -0,0 +1,67 @@
+use super::{BuildArgs, GasArgs};
+use anyhow::{Context, Result};
+use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use txs::{
+    constant::{DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT, DEFAULT_OFFLINE_EXPIRATION_SECS},
+    extension::client_ext::ClientExt,
+    rest_client::Client,
+    types::{account_address::AccountAddress, chain_id::ChainId},
+    util::write_transaction_file,
+};
+use zapatos_sdk::transaction_builder::TransactionBuilder;
+
+/// Online step of offline signing: build an unsigned transaction and write it to `output`.
+/// The sequence number and chain id are fetched from chain unless given.
+pub async fn run(
+    sender: &str,
+    function_id: &str,
+    type_args: Option<String>,
+    args: Option<String>,
+    build: &BuildArgs,
+    gas: &GasArgs,
+    output: &Path,
+) -> Result<()> {
+    let sender = AccountAddress::from_hex_literal(sender)
+        .context(format!("Failed to parse the sender address {sender}"))?;
+
+    // the args are checked against the ABI on chain, only signing is offline
+    let client = Client::default()?;
+    let payload = client
+        .entry_function_payload_ext(function_id, type_args, args)
+        .await?;
+    let sequence_number = match build.sequence_number {
+        Some(seq) => seq,
+        None => client.get_sequence_number(sender).await?,
+    };
+    let chain_id = match build.chain_id {
+        Some(chain_id) => chain_id,
+        None => client.get_index().await?.inner().chain_id,
+    };
+
+    let expiration_timestamp_secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()
+        + build
+            .expiration_secs
+            .unwrap_or(DEFAULT_OFFLINE_EXPIRATION_SECS);
+
+    let raw_trans =
+        TransactionBuilder::new(payload, expiration_timestamp_secs, ChainId::new(chain_id))
+            .sender(sender)
+            .sequence_number(sequence_number)
+            .max_gas_amount(gas.max_gas.unwrap_or(DEFAULT_MAX_GAS_AMOUNT))
+            .gas_unit_price(gas.gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE))
+            .build();
+
+    println!(
+        "{}",
+        raw_trans
+            .format_for_client(|_| String::new())
+            .replace('\t', "    ")
+    );
+    write_transaction_file(output, &raw_trans)?;
+    println!("Unsigned transaction written to {}", output.display());
+    Ok(())
+}
@@ -1,34 +1,76 @@
+use super::{send_payload, GasArgs, SimulateArgs, TransactionArgs};
 use anyhow::Result;
-use libra_config::extension::client_ext::{ClientExt as ConfigClientExt, DEFAULT_TIMEOUT_SECS};
+use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
+use std::path::Path;
 use txs::{
-    constant::{DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT},
-    crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
-    extension::{
-        client_ext::{ClientExt, TransactionOptions},
-        ed25519_private_key_ext::Ed25519PrivateKeyExt,
-    },
+    extension::client_ext::{script_payload, ClientExt, TransactionOptions},
     rest_client::Client,
-    types::transaction::SignedTransaction,
+    signer::SignerSource,
+    simulation::SimulationReport,
+    types::{
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
 };
 
+/// What the transaction executes
+pub enum Call<'a> {
+    EntryFunction(&'a str),
+    Script(&'a Path),
+}
+
 pub async fn run(
-    function_id: &str,
-    private_key: &str,
+    call: Call<'_>,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
     type_args: Option<String>,
     args: Option<String>,
-    max_gas: Option<u64>,
-    gas_unit_price: Option<u64>,
+    gas: &GasArgs,
+    simulation: &SimulateArgs,
 ) -> Result<SignedTransaction> {
+    let (client, mut account, payload, options) =
+        prepare(call, signer, transaction, type_args, args, gas, simulation).await?;
+
+    client.sign_payload(&mut account, payload, options).await
+}
+
+/// Dry run the transaction instead of signing it
+pub async fn simulate(
+    call: Call<'_>,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
+    type_args: Option<String>,
+    args: Option<String>,
+    gas: &GasArgs,
+    simulation: &SimulateArgs,
+) -> Result<SimulationReport> {
+    let (client, account, payload, options) =
+        prepare(call, signer, transaction, type_args, args, gas, simulation).await?;
+
+    client.simulate_payload(&account, payload, &options).await
+}
+
+async fn prepare(
+    call: Call<'_>,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
+    type_args: Option<String>,
+    args: Option<String>,
+    gas: &GasArgs,
+    simulation: &SimulateArgs,
+) -> Result<(Client, LocalAccount, TransactionPayload, TransactionOptions)> {
     let client = Client::default()?;
-    let private_key = Ed25519PrivateKey::from_encoded_string(private_key)?;
-    let mut account = private_key.get_account(None).await?;
-    let options = TransactionOptions {
-        max_gas_amount: max_gas.unwrap_or(DEFAULT_MAX_GAS_AMOUNT),
-        gas_unit_price: gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE),
-        timeout_secs: DEFAULT_TIMEOUT_SECS,
+    let account = signer.get_account(transaction.sequence_number).await?;
+    let payload = match call {
+        Call::EntryFunction(function_id) => {
+            client
+                .entry_function_payload_ext(function_id, type_args, args)
+                .await?
+        }
+        Call::Script(script_file) => script_payload(script_file, type_args, args)?,
     };
+    let options =
+        send_payload::options(&client, &account, &payload, gas, transaction, simulation).await?;
 
-    client
-        .generate_transaction(&mut account, function_id, type_args, args, options)
-        .await
+    Ok((client, account, payload, options))
 }
@@ -0,0 +1,230 @@
+use super::{send_payload, GasArgs, SignerArgs, SimulateArgs, TransactionArgs};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use libra_config::extension::client_ext::ClientExt;
+use libra_wallet::validator_files::validator_home_dir;
+use query::output::{format_output, OutputFormat};
+use std::path::PathBuf;
+use txs::{
+    crypto::HashValue,
+    governance::{
+        create_proposal_payload, get_proposal, list_proposals, resolve_payload, script_hash,
+        vote_payload, Voter,
+    },
+    rest_client::Client,
+    types::transaction::TransactionPayload,
+};
+
+#[derive(clap::Subcommand)]
+pub(super) enum GovernanceAction {
+    /// List the proposals open for voting or waiting to be resolved, with their tallies
+    List {
+        /// Include the resolved and failed proposals
+        #[clap(long)]
+        all: bool,
+    },
+
+    /// Propose running the governance script with this execution hash
+    Propose {
+        /// sha3-256 hash of the compiled script, in hex
+        #[clap(long)]
+        execution_hash: String,
+
+        /// Where the description of the proposal is published
+        #[clap(long, default_value = "")]
+        metadata_url: String,
+
+        /// sha3-256 hash of the description, in hex
+        #[clap(long)]
+        metadata_hash: Option<String>,
+
+        #[clap(flatten)]
+        voter: VoterArgs,
+    },
+
+    /// Vote on a proposal with the stake pool of owner.yaml
+    Vote {
+        proposal_id: u64,
+
+        #[clap(value_enum)]
+        ballot: Ballot,
+
+        #[clap(flatten)]
+        voter: VoterArgs,
+    },
+
+    /// Run the script of an approved proposal
+    Resolve {
+        proposal_id: u64,
+
+        /// The compiled script the proposal was created with
+        #[clap(long)]
+        script_file: PathBuf,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(super) enum Ballot {
+    Yes,
+    No,
+}
+
+/// The voter of a stake pool, and the gas to pay
+#[derive(clap::Args)]
+pub(super) struct VoterArgs {
+    /// Directory of owner.yaml, defaults to $HOME/.libra
+    #[clap(long)]
+    home_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+
+    #[clap(flatten)]
+    transaction: TransactionArgs,
+
+    #[clap(flatten)]
+    gas: GasArgs,
+}
+
+impl VoterArgs {
+    fn voter(&self) -> Result<Voter> {
+        Voter::read(&validator_home_dir(self.home_dir.to_owned())?)
+    }
+}
+
+pub(super) async fn run(action: &GovernanceAction, output: OutputFormat) -> Result<()> {
+    let client = Client::default()?;
+    match action {
+        GovernanceAction::List { all } => {
+            let proposals = list_proposals(&client, *all).await?;
+            println!("{}", format_output(&proposals, output)?);
+            Ok(())
+        }
+        GovernanceAction::Propose {
+            execution_hash,
+            metadata_url,
+            metadata_hash,
+            voter,
+        } => {
+            let execution_hash = HashValue::from_hex_literal(execution_hash)
+                .or_else(|_| HashValue::from_hex(execution_hash))
+                .context(format!("Invalid execution hash {execution_hash}"))?;
+            let metadata_hash = match metadata_hash {
+                Some(hash) => hex::decode(hash.trim_start_matches("0x"))
+                    .context(format!("Invalid metadata hash {hash}"))?,
+                None => vec![],
+            };
+            let pool_address = voter.voter()?.pool_address;
+            let payload = create_proposal_payload(
+                pool_address,
+                execution_hash,
+                metadata_url,
+                &metadata_hash,
+            )?;
+            send_as_voter(&client, voter, payload, output).await?;
+            if voter.transaction.no_wait {
+                return Ok(());
+            }
+
+            let proposals = list_proposals(&client, false).await?;
+            println!("{}", format_output(&proposals, output)?);
+            Ok(())
+        }
+        GovernanceAction::Vote {
+            proposal_id,
+            ballot,
+            voter,
+        } => {
+            let pool_address = voter.voter()?.pool_address;
+            let should_pass = matches!(ballot, Ballot::Yes);
+            let payload = vote_payload(pool_address, *proposal_id, should_pass)?;
+            send_as_voter(&client, voter, payload, output).await?;
+            if voter.transaction.no_wait {
+                return Ok(());
+            }
+
+            let proposal = get_proposal(&client, *proposal_id).await?;
+            println!("{}", format_output(&proposal, output)?);
+            Ok(())
+        }
+        GovernanceAction::Resolve {
+            proposal_id,
+            script_file,
+            signer,
+            transaction,
+            gas,
+        } => {
+            let proposal = get_proposal(&client, *proposal_id).await?;
+            if proposal.state != "succeeded" || proposal.is_resolved {
+                bail!(
+                    "Proposal {proposal_id} is {}{}, only approved proposals can be resolved",
+                    proposal.state,
+                    if proposal.is_resolved {
+                        " and resolved"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            let code = std::fs::read(script_file)
+                .context(format!("Failed to read {}", script_file.display()))?;
+            let hash = script_hash(&code).to_hex_literal();
+            if hash != proposal.execution_hash {
+                bail!(
+                    "{} hashes to {hash}, but proposal {proposal_id} executes {}",
+                    script_file.display(),
+                    proposal.execution_hash
+                );
+            }
+
+            let mut account = signer
+                .source()
+                .get_account(transaction.sequence_number)
+                .await?;
+            send_payload::run(
+                &client,
+                &mut account,
+                resolve_payload(code, *proposal_id),
+                gas,
+                transaction,
+                &SimulateArgs::default(),
+                output,
+            )
+            .await
+        }
+    }
+}
+
+/// Sign as the voter of the pool, which the signing key has to belong to
+async fn send_as_voter(
+    client: &Client,
+    args: &VoterArgs,
+    payload: TransactionPayload,
+    output: OutputFormat,
+) -> Result<()> {
+    let mut account = args
+        .signer
+        .source()
+        .get_account(args.transaction.sequence_number)
+        .await?;
+    args.voter()?.check_signer(account.address())?;
+    send_payload::run(
+        client,
+        &mut account,
+        payload,
+        &args.gas,
+        &args.transaction,
+        &SimulateArgs::default(),
+        output,
+    )
+    .await
+}
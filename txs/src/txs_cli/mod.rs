@@ -1,14 +1,40 @@
 use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
+use generate_transaction::Call;
+use governance::GovernanceAction;
 use indoc::indoc;
-use txs::util::format_signed_transaction;
+use query::output::{format_output, OutputFormat};
+use std::path::PathBuf;
+use txs::{
+    batch::BatchOptions,
+    constant::{
+        DEFAULT_BATCH_SIZE, DEFAULT_EXPIRATION_SECS, DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT,
+    },
+    extension::client_ext::TransactionOptions,
+    signer::SignerSource,
+    types::transaction::SignedTransaction,
+    util::{format_signed_transaction, read_transaction_file},
+};
+use validator::ValidatorAction;
 
+mod batch;
+mod build_transaction;
 mod create_account;
 mod demo;
 mod generate_transaction;
+mod governance;
+mod multisig;
+mod progress;
+mod publish;
+mod replace_transaction;
+mod rotate_key;
+mod send_payload;
+mod sign_transaction;
+mod status;
 mod submit_transaction;
 mod transfer_coin;
+mod validator;
 mod view;
 
 #[derive(Parser)]
@@ -16,6 +42,10 @@ mod view;
 pub struct TxsCli {
     #[clap(subcommand)]
     subcommand: Option<Subcommand>,
+
+    /// Output format of generated transactions and view results
+    #[clap(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
 }
 
 #[derive(clap::Subcommand)]
@@ -44,21 +74,220 @@ enum Subcommand {
         #[clap(short, long)]
         amount: u64,
 
-        /// Private key of the account to withdraw money from
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+
+        #[clap(flatten)]
+        simulation: SimulateArgs,
+    },
+
+    /// Generate a transaction that executes an Entry function, or a compiled script, on-chain
+    GenerateTransaction {
+        #[clap(
+            short,
+            long,
+            required_unless_present = "script_file",
+            help = indoc!{r#"
+                Function identifier has the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>
+
+                Example:
+                0x1::coin::transfer
+            "#}
+        )]
+        function_id: Option<String>,
+
+        /// Compiled .mv script to execute instead of an Entry function. Its args are parsed as given, without an ABI
+        #[clap(long, conflicts_with = "function_id")]
+        script_file: Option<PathBuf>,
+
+        #[clap(
+            short,
+            long,
+            help = indoc!{ r#"
+                Type arguments separated by commas
+
+                Example: 
+                'u8, u16, u32, u64, u128, u256, bool, address, vector<u8>, signer'
+                '0x1::aptos_coin::AptosCoin'
+            "#}
+        )]
+        type_args: Option<String>,
+
+        #[clap(
+            short,
+            long,
+            help = indoc!{ r#"
+                Function arguments separated by commas, or as a json array. They are checked
+                against the function's ABI
+
+                Example:
+                '0x1, true, 12, 24_u8, x"123456"'
+                '["0x1", [1, 2], "a, b"]'
+            "#}
+        )]
+        args: Option<String>,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        /// Submit the generated transaction to the blockchain. The signed transaction is then printed to stderr
         #[clap(short, long)]
-        private_key: String,
+        submit: bool,
+
+        #[clap(flatten)]
+        simulation: SimulateArgs,
+    },
+
+    /// Sign and submit many transfers or entry function calls from a csv or json manifest
+    Batch {
+        #[clap(
+            long,
+            help = indoc!{r#"
+                Manifest of transactions. A csv file with a header row, or a json array, with the columns
+                to_account, amount for transfers, or function_id, type_args, args for entry function calls
+            "#}
+        )]
+        manifest: PathBuf,
+
+        /// Report of each row's hash and status, read back to resume a partial run. Defaults to <MANIFEST>.report.json
+        #[clap(long)]
+        report: Option<PathBuf>,
+
+        /// Transactions submitted per batch request. Each batch commits before the next is sent,
+        /// except with --no-wait, which submits all of them back to back
+        #[clap(long, default_value_t = DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+
+        /// Send the rows which failed on a previous run again. They are skipped by default,
+        /// like the rows which succeeded
+        #[clap(long)]
+        retry_failed: bool,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+    },
+
+    /// Publish a compiled Move package, or module bytecode files, from the signing account
+    ///
+    /// The package is published in a single transaction, which limits its code and metadata
+    /// to 60000 bytes. Larger packages are rejected, and have to be split into several.
+    Publish {
+        /// A package compiled by the Move CLI: the build/<PACKAGE> directory holding package-metadata.bcs and bytecode_modules
+        #[clap(long, conflicts_with = "modules")]
+        package_dir: Option<PathBuf>,
+
+        /// Compiled .mv module files separated by commas, published as one package
+        #[clap(long, value_delimiter = ',', requires = "package_name")]
+        modules: Vec<PathBuf>,
+
+        /// Name of the package the module files are published as
+        #[clap(long)]
+        package_name: Option<String>,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+
+        #[clap(flatten)]
+        simulation: SimulateArgs,
+    },
+
+    /// Rotate the authentication key of the signing account to a new key
+    RotateKey {
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        /// private-keys.yaml written by libra-wallet holding the new account key
+        #[clap(long)]
+        new_key_file: PathBuf,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+    },
+
+    /// Submit the stake transactions of a validator described by libra-wallet's operator.yaml and owner.yaml
+    Validator {
+        #[clap(value_enum)]
+        action: ValidatorAction,
 
-        /// Maximum number of gas units to be used to send this transaction
+        /// Directory of the validator files, defaults to $HOME/.libra
+        #[clap(long)]
+        home_dir: Option<PathBuf>,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        #[clap(flatten)]
+        transaction: TransactionArgs,
+
+        #[clap(flatten)]
+        gas: GasArgs,
+
+        /// Submit the address update without asking to confirm the change
+        #[clap(long)]
+        yes: bool,
+    },
+
+    /// List, propose, vote on and resolve on chain governance proposals
+    Governance {
+        #[clap(subcommand)]
+        action: GovernanceAction,
+    },
+
+    /// Re-sign a transaction stuck in the mempool at the same sequence number with a higher gas unit price
+    ReplaceTransaction {
+        /// Hash of the pending transaction
+        #[clap(long)]
+        hash: String,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        /// Seconds until the replacement expires
+        #[clap(long, default_value_t = DEFAULT_EXPIRATION_SECS)]
+        expiration_secs: u64,
+
+        /// Maximum number of gas units, defaults to the one of the pending transaction
         #[clap(short, long)]
         max_gas: Option<u64>,
 
-        /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
+        /// The amount of coins to pay for 1 gas unit, defaults to the pending transaction's raised by 20%
         #[clap(short, long)]
         gas_unit_price: Option<u64>,
     },
 
-    /// Generate a transaction that executes an Entry function on-chain
-    GenerateTransaction {
+    /// Build an unsigned transaction that executes an Entry function, to be signed offline
+    BuildTransaction {
+        /// Address of the account sending the transaction
+        #[clap(long)]
+        sender: String,
+
         #[clap(
             short,
             long,
@@ -88,29 +317,116 @@ enum Subcommand {
             short,
             long,
             help = indoc!{ r#"
-                Function arguments separated by commas
+                Function arguments separated by commas, or as a json array. They are checked
+                against the function's ABI
 
                 Example:
                 '0x1, true, 12, 24_u8, x"123456"'
+                '["0x1", [1, 2], "a, b"]'
             "#}
         )]
         args: Option<String>,
 
-        /// Maximum amount of gas units to be used to send this transaction
-        #[clap(short, long)]
-        max_gas: Option<u64>,
+        #[clap(flatten)]
+        build: BuildArgs,
 
-        /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
-        #[clap(short, long)]
-        gas_unit_price: Option<u64>,
+        #[clap(flatten)]
+        gas: GasArgs,
 
-        /// Private key to sign the transaction
-        #[clap(short, long)]
-        private_key: String,
+        /// File to write the unsigned transaction to, as json if it ends with .json and BCS otherwise
+        #[clap(long)]
+        output_file: PathBuf,
+    },
 
-        /// Submit the generated transaction to the blockchain
-        #[clap(short, long)]
-        submit: bool,
+    /// Sign a transaction built by build-transaction. Does not need network access
+    SignTransaction {
+        /// File of the unsigned transaction
+        #[clap(long)]
+        input_file: PathBuf,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        /// File to write the signed transaction to, as json if it ends with .json and BCS otherwise
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+
+    /// Derive the authentication key and address of a k-of-n MultiEd25519 account
+    CreateMultisig {
+        /// Public keys of the account holders separated by commas
+        #[clap(long)]
+        public_keys: String,
+
+        /// Number of signatures needed to authorize a transaction
+        #[clap(long)]
+        threshold: u8,
+
+        /// File to write the public keys and threshold to, used by combine-signatures
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Sign a transaction built by build-transaction as one of several signers
+    SignPartial {
+        /// File of the unsigned transaction
+        #[clap(long)]
+        input_file: PathBuf,
+
+        #[clap(flatten)]
+        signer: SignerArgs,
+
+        /// Addresses of the secondary signers of a multi-agent transaction, separated by commas
+        #[clap(long, value_delimiter = ',')]
+        secondary_signers: Vec<String>,
+
+        /// File to write the partial signature to
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+
+    /// Combine partial signatures into a transaction for submit-transaction
+    CombineSignatures {
+        /// File of the unsigned transaction
+        #[clap(long)]
+        input_file: PathBuf,
+
+        /// Partial signature files of the sender, separated by commas
+        #[clap(long, value_delimiter = ',', required = true)]
+        signatures: Vec<PathBuf>,
+
+        /// File written by create-multisig when the sender is a k-of-n account
+        #[clap(long)]
+        multisig_file: Option<PathBuf>,
+
+        /// Addresses of the secondary signers of a multi-agent transaction, separated by commas
+        #[clap(long, value_delimiter = ',')]
+        secondary_signers: Vec<String>,
+
+        /// Partial signature files of the secondary signers, in the same order
+        #[clap(long, value_delimiter = ',')]
+        secondary_signatures: Vec<PathBuf>,
+
+        /// File to write the signed transaction to, as json if it ends with .json and BCS otherwise
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+
+    /// Submit a transaction signed by sign-transaction or combine-signatures
+    SubmitTransaction {
+        /// File of the signed transaction
+        #[clap(long)]
+        input_file: PathBuf,
+
+        /// Print the hash once submitted without waiting for the transaction to commit
+        #[clap(long)]
+        no_wait: bool,
+    },
+
+    /// Poll a transaction until it commits or expires, and show its version, gas used and VM status
+    Status {
+        /// Hash of the transaction
+        hash: String,
     },
 
     /// Execute a View function on-chain
@@ -144,10 +460,12 @@ enum Subcommand {
             short,
             long,
             help = indoc!{ r#"
-                Function arguments separated by commas
+                Function arguments separated by commas, or as a json array. They are checked
+                against the function's ABI
 
                 Example:
                 '0x1, true, 12, 24_u8, x"123456"'
+                '["0x1", [1, 2], "a, b"]'
             "#}
         )]
         args: Option<String>,
@@ -165,61 +483,364 @@ impl TxsCli {
             Some(Subcommand::TransferCoins {
                 to_account,
                 amount,
-                private_key,
-                max_gas,
-                gas_unit_price,
+                signer,
+                transaction,
+                gas,
+                simulation,
             }) => {
                 transfer_coin::run(
                     to_account,
                     amount.to_owned(),
-                    private_key,
-                    max_gas.to_owned(),
-                    gas_unit_price.to_owned(),
+                    &signer.source(),
+                    transaction,
+                    gas,
+                    simulation,
+                    self.output,
                 )
                 .await
             }
             Some(Subcommand::GenerateTransaction {
                 function_id,
+                script_file,
                 type_args,
                 args,
-                max_gas,
-                gas_unit_price,
-                private_key,
+                gas,
+                signer,
+                transaction,
                 submit,
+                simulation,
             }) => {
-                println!("====================");
+                if let OutputFormat::Table = self.output {
+                    println!("====================");
+                }
+                let call = || match (function_id, script_file) {
+                    (_, Some(script_file)) => Call::Script(script_file),
+                    (Some(function_id), None) => Call::EntryFunction(function_id),
+                    (None, None) => unreachable!("clap requires one of them"),
+                };
+                if simulation.simulate {
+                    let report = generate_transaction::simulate(
+                        call(),
+                        &signer.source(),
+                        transaction,
+                        type_args.to_owned(),
+                        args.to_owned(),
+                        gas,
+                        simulation,
+                    )
+                    .await?;
+                    println!("{}", format_output(&report, self.output)?);
+                    return Ok(());
+                }
+
                 let signed_trans = generate_transaction::run(
-                    function_id,
-                    private_key,
+                    call(),
+                    &signer.source(),
+                    transaction,
                     type_args.to_owned(),
                     args.to_owned(),
-                    max_gas.to_owned(),
-                    gas_unit_price.to_owned(),
+                    gas,
+                    simulation,
                 )
                 .await?;
 
-                println!("{}", format_signed_transaction(&signed_trans));
-
-                if *submit {
-                    println!("{}", "Submitting transaction...".green().bold());
-                    submit_transaction::run(&signed_trans).await?;
-                    println!("Success!");
+                let preview = match self.output {
+                    OutputFormat::Table => format_signed_transaction(&signed_trans),
+                    format => format_output(&signed_trans, format)?,
+                };
+                // when submitting, stdout carries only the status, as one document
+                if !*submit {
+                    println!("{preview}");
+                    return Ok(());
+                }
+                eprintln!("{preview}");
+                eprintln!("{}", "Submitting transaction...".green().bold());
+                submit_transaction::run(&signed_trans, transaction.no_wait, self.output).await?;
+                Ok(())
+            }
+            Some(Subcommand::BuildTransaction {
+                sender,
+                function_id,
+                type_args,
+                args,
+                build,
+                gas,
+                output_file,
+            }) => {
+                build_transaction::run(
+                    sender,
+                    function_id,
+                    type_args.to_owned(),
+                    args.to_owned(),
+                    build,
+                    gas,
+                    output_file,
+                )
+                .await
+            }
+            Some(Subcommand::SignTransaction {
+                input_file,
+                signer,
+                output_file,
+            }) => {
+                let (signed_trans, warnings) =
+                    sign_transaction::run(input_file, &signer.source(), output_file)?;
+                print_warnings(&warnings);
+                match self.output {
+                    OutputFormat::Table => println!("{}", format_signed_transaction(&signed_trans)),
+                    format => println!("{}", format_output(&signed_trans, format)?),
+                }
+                Ok(())
+            }
+            Some(Subcommand::Batch {
+                manifest,
+                report,
+                batch_size,
+                retry_failed,
+                signer,
+                transaction,
+                gas,
+            }) => {
+                let options = BatchOptions {
+                    transaction: gas.options(transaction),
+                    batch_size: *batch_size,
+                    wait: !transaction.no_wait,
+                    retry_failed: *retry_failed,
+                };
+                batch::run(
+                    manifest,
+                    report.to_owned(),
+                    &signer.source(),
+                    transaction,
+                    &options,
+                    self.output,
+                )
+                .await
+            }
+            Some(Subcommand::CreateMultisig {
+                public_keys,
+                threshold,
+                output_file,
+            }) => multisig::create(public_keys, *threshold, output_file.as_deref()),
+            Some(Subcommand::SignPartial {
+                input_file,
+                signer,
+                secondary_signers,
+                output_file,
+            }) => multisig::sign(input_file, &signer.source(), secondary_signers, output_file),
+            Some(Subcommand::CombineSignatures {
+                input_file,
+                signatures,
+                multisig_file,
+                secondary_signers,
+                secondary_signatures,
+                output_file,
+            }) => {
+                let (signed_trans, warnings) = multisig::combine(
+                    input_file,
+                    signatures,
+                    multisig_file.as_deref(),
+                    secondary_signers,
+                    secondary_signatures,
+                    output_file,
+                )?;
+                print_warnings(&warnings);
+                match self.output {
+                    OutputFormat::Table => println!("{}", format_signed_transaction(&signed_trans)),
+                    format => println!("{}", format_output(&signed_trans, format)?),
                 }
                 Ok(())
             }
+            Some(Subcommand::Publish {
+                package_dir,
+                modules,
+                package_name,
+                signer,
+                transaction,
+                gas,
+                simulation,
+            }) => {
+                let package =
+                    publish::package(package_dir.as_deref(), modules, package_name.as_deref())?;
+                publish::run(
+                    &package,
+                    &signer.source(),
+                    transaction,
+                    gas,
+                    simulation,
+                    self.output,
+                )
+                .await
+            }
+            Some(Subcommand::RotateKey {
+                signer,
+                transaction,
+                new_key_file,
+                gas,
+            }) => rotate_key::run(&signer.source(), new_key_file, transaction, gas).await,
+            Some(Subcommand::Validator {
+                action,
+                home_dir,
+                signer,
+                transaction,
+                gas,
+                yes,
+            }) => {
+                validator::run(
+                    *action,
+                    home_dir.to_owned(),
+                    &signer.source(),
+                    transaction,
+                    gas,
+                    *yes,
+                    self.output,
+                )
+                .await
+            }
+            Some(Subcommand::Governance { action }) => governance::run(action, self.output).await,
+            Some(Subcommand::ReplaceTransaction {
+                hash,
+                signer,
+                expiration_secs,
+                max_gas,
+                gas_unit_price,
+            }) => {
+                replace_transaction::run(
+                    hash,
+                    &signer.source(),
+                    *expiration_secs,
+                    max_gas.to_owned(),
+                    gas_unit_price.to_owned(),
+                    self.output,
+                )
+                .await
+            }
+            Some(Subcommand::SubmitTransaction {
+                input_file,
+                no_wait,
+            }) => {
+                let signed_trans: SignedTransaction = read_transaction_file(input_file)?;
+                eprintln!("{}", "Submitting transaction...".green().bold());
+                submit_transaction::run(&signed_trans, *no_wait, self.output).await
+            }
+            Some(Subcommand::Status { hash }) => status::run(hash, self.output).await,
             Some(Subcommand::View {
                 function_id,
                 type_args,
                 args,
             }) => {
-                println!("====================");
-                println!(
-                    "{}",
-                    view::run(function_id, type_args.to_owned(), args.to_owned()).await?
-                );
+                let result = view::run(function_id, type_args.to_owned(), args.to_owned()).await?;
+                if let OutputFormat::Table = self.output {
+                    println!("=======OUTPUT=======");
+                }
+                println!("{}", format_output(&result, self.output)?);
                 Ok(())
             }
             _ => Ok(()),
         }
     }
 }
+
+/// The gas a transaction may use, and the price paid for it
+#[derive(clap::Args)]
+pub struct GasArgs {
+    /// Maximum number of gas units to be used to send this transaction
+    #[clap(short, long)]
+    max_gas: Option<u64>,
+
+    /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
+    #[clap(short, long)]
+    gas_unit_price: Option<u64>,
+}
+
+impl GasArgs {
+    fn options(&self, transaction: &TransactionArgs) -> TransactionOptions {
+        TransactionOptions {
+            max_gas_amount: self.max_gas.unwrap_or(DEFAULT_MAX_GAS_AMOUNT),
+            gas_unit_price: self.gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE),
+            expiration_secs: transaction.expiration_secs,
+        }
+    }
+}
+
+/// Whether to only simulate the transaction, and whether to estimate its gas first
+#[derive(clap::Args, Default)]
+pub struct SimulateArgs {
+    /// Simulate the transaction and show the outcome, gas used, balance changes and events, without submitting it
+    #[clap(long)]
+    simulate: bool,
+
+    /// Set the max gas from a simulation plus a margin, and the gas unit price from the node's estimate, unless given
+    #[clap(long)]
+    estimate_gas: bool,
+}
+
+/// The sequence number, chain id and expiration of a transaction built for offline signing
+#[derive(clap::Args)]
+pub struct BuildArgs {
+    /// Sequence number of the sender, fetched from chain if not given
+    #[clap(long)]
+    sequence_number: Option<u64>,
+
+    /// Chain id of the network, fetched from chain if not given
+    #[clap(long)]
+    chain_id: Option<u8>,
+
+    /// Seconds from now until the transaction expires, defaults to one hour
+    #[clap(long)]
+    expiration_secs: Option<u64>,
+}
+
+/// The sequence number and expiration of the transaction to sign
+#[derive(clap::Args)]
+pub struct TransactionArgs {
+    /// Sign with this sequence number instead of the account's next one on chain
+    #[clap(long)]
+    sequence_number: Option<u64>,
+
+    /// Seconds until the transaction expires
+    #[clap(long, default_value_t = DEFAULT_EXPIRATION_SECS)]
+    expiration_secs: u64,
+
+    /// Print the hash once submitted without waiting for the transaction to commit, to follow it with `txs status`
+    #[clap(long)]
+    no_wait: bool,
+}
+
+/// Where to get the key of the signing account. When none is given, the default profile
+/// is used if there is a config.yaml, otherwise the key is prompted for
+#[derive(clap::Args)]
+struct SignerArgs {
+    /// Sign with the private key of this libra-config profile
+    #[clap(long, conflicts_with_all = ["key_file", "private_key_env"])]
+    profile: Option<String>,
+
+    /// Sign with the account key of a private-keys.yaml file written by libra-wallet
+    #[clap(long, conflicts_with = "private_key_env")]
+    key_file: Option<PathBuf>,
+
+    /// Read the hex encoded private key from this environment variable
+    #[clap(long)]
+    private_key_env: Option<String>,
+}
+
+impl SignerArgs {
+    fn source(&self) -> SignerSource {
+        if let Some(path) = &self.key_file {
+            SignerSource::KeyFile(path.to_owned())
+        } else if let Some(var) = &self.private_key_env {
+            SignerSource::Env(var.to_owned())
+        } else if let Some(profile) = &self.profile {
+            SignerSource::Profile(Some(profile.to_owned()))
+        } else {
+            SignerSource::fallback()
+        }
+    }
+}
+
+/// Warnings go to stderr, to keep stdout parsable with --output json or yaml
+fn print_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("{} {warning}", "Warning:".yellow().bold());
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use txs::{
+    multisig::{
+        combine_signatures, MultisigConfig, PartialSignature, SenderSignatures, SigningMessage,
+    },
+    signer::SignerSource,
+    types::{
+        account_address::AccountAddress,
+        transaction::{RawTransaction, SignedTransaction},
+    },
+    util::{read_transaction_file, write_transaction_file},
+};
+
+/// Derive the authentication key and address of a k-of-n account
+pub fn create(public_keys: &str, threshold: u8, output: Option<&Path>) -> Result<()> {
+    let config = MultisigConfig::new(public_keys, threshold)?;
+    println!(
+        "{}-of-{} multisig",
+        config.threshold,
+        config.public_keys.len()
+    );
+    println!("Authentication key: {}", config.authentication_key()?);
+    println!("Account address: {}", config.address()?.to_hex_literal());
+    if let Some(output) = output {
+        config.write(output)?;
+        println!("Multisig keys written to {}", output.display());
+    }
+    Ok(())
+}
+
+/// Offline step for each key holder: sign the transaction in `input` and write the
+/// partial signature to `output`
+pub fn sign(
+    input: &Path,
+    signer: &SignerSource,
+    secondary_signers: &[String],
+    output: &Path,
+) -> Result<()> {
+    let raw_trans: RawTransaction = read_transaction_file(input)?;
+    let message = SigningMessage::new(raw_trans, parse_addresses(secondary_signers)?);
+    let (private_key, _) = signer.load()?;
+
+    write_transaction_file(output, &message.sign(&private_key)?)?;
+    eprintln!("Partial signature written to {}", output.display());
+    Ok(())
+}
+
+/// Combine the partial signatures into a transaction for submit-transaction
+pub fn combine(
+    input: &Path,
+    signatures: &[PathBuf],
+    multisig_file: Option<&Path>,
+    secondary_signers: &[String],
+    secondary_signatures: &[PathBuf],
+    output: &Path,
+) -> Result<(SignedTransaction, Vec<String>)> {
+    let raw_trans: RawTransaction = read_transaction_file(input)?;
+    let message = SigningMessage::new(raw_trans, parse_addresses(secondary_signers)?);
+    let partials = read_partials(signatures)?;
+
+    let sender = match multisig_file {
+        Some(path) => SenderSignatures::Multisig(MultisigConfig::read(path)?, partials),
+        None => match <[PartialSignature; 1]>::try_from(partials) {
+            Ok([partial]) => SenderSignatures::Single(partial),
+            Err(_) => bail!("Without a multisig file the sender needs exactly one signature"),
+        },
+    };
+
+    let (signed_trans, warnings) =
+        combine_signatures(message, sender, read_partials(secondary_signatures)?)?;
+    write_transaction_file(output, &signed_trans)?;
+    eprintln!("Signed transaction written to {}", output.display());
+    Ok((signed_trans, warnings))
+}
+
+fn read_partials(paths: &[PathBuf]) -> Result<Vec<PartialSignature>> {
+    paths
+        .iter()
+        .map(|path| read_transaction_file(path))
+        .collect()
+}
+
+fn parse_addresses(addresses: &[String]) -> Result<Vec<AccountAddress>> {
+    addresses
+        .iter()
+        .map(|address| {
+            AccountAddress::from_hex_literal(address)
+                .context(format!("Failed to parse the address {address}"))
+        })
+        .collect()
+}
@@ -0,0 +1,29 @@
+//! spinners in the style of genesis-tools' OLProgress
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+/// A ticking spinner for waits of unknown length, cleared with `finish_and_clear`
+pub fn spinner(msg: String) -> ProgressBar {
+    let style = ProgressStyle::with_template("{msg} {spinner:.blue}")
+        .unwrap()
+        .tick_strings(&[
+            "      ",
+            "·     ",
+            "··    ",
+            "···   ",
+            "····  ",
+            "····· ",
+            "······",
+            " ·····",
+            "  ····",
+            "   ···",
+            "    ··",
+            "     ·",
+        ]);
+    let spinner = ProgressBar::new_spinner()
+        .with_style(style)
+        .with_message(msg);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
@@ -0,0 +1,52 @@
+use super::{send_payload, GasArgs, SimulateArgs, TransactionArgs};
+use anyhow::{bail, Context, Result};
+use libra_config::extension::client_ext::ClientExt;
+use query::output::OutputFormat;
+use std::path::{Path, PathBuf};
+use txs::{publish::Package, rest_client::Client, signer::SignerSource};
+
+/// Load a compiled package directory, or bare module files
+pub fn package(
+    package_dir: Option<&Path>,
+    modules: &[PathBuf],
+    package_name: Option<&str>,
+) -> Result<Package> {
+    match (package_dir, modules.is_empty()) {
+        (Some(dir), true) => Package::from_dir(dir),
+        (None, false) => Package::from_modules(
+            package_name.context("A package name is needed to publish module files")?,
+            modules,
+        ),
+        _ => bail!("Give either a package directory or module files"),
+    }
+}
+
+/// Publish a package from the signing account
+pub async fn run(
+    package: &Package,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
+    gas: &GasArgs,
+    simulation: &SimulateArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = Client::default()?;
+    let mut from_account = signer.get_account(transaction.sequence_number).await?;
+    package.check(from_account.address())?;
+    eprintln!(
+        "Publishing {} modules, {} bytes",
+        package.modules.len(),
+        package.size()
+    );
+
+    send_payload::run(
+        &client,
+        &mut from_account,
+        package.payload()?,
+        gas,
+        transaction,
+        simulation,
+        output,
+    )
+    .await
+}
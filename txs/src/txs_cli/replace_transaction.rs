@@ -0,0 +1,56 @@
+use super::submit_transaction;
+use anyhow::{bail, Result};
+use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
+use query::output::OutputFormat;
+use txs::{
+    extension::client_ext::{
+        replacement_gas_unit_price, transaction_builder, ClientExt, TransactionOptions,
+    },
+    rest_client::Client,
+    signer::SignerSource,
+    status::parse_hash,
+};
+
+/// Re-sign the payload of a pending transaction at its sequence number with a higher gas
+/// unit price, so the mempool drops it for the replacement
+pub async fn run(
+    hash: &str,
+    signer: &SignerSource,
+    expiration_secs: u64,
+    max_gas: Option<u64>,
+    gas_unit_price: Option<u64>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = Client::default()?;
+    let hash = parse_hash(hash)?;
+    let pending = client.get_pending_transaction(hash).await?;
+
+    let mut account = signer.get_account(Some(pending.sequence_number())).await?;
+    if account.address() != pending.sender() {
+        bail!(
+            "Transaction {hash} was sent by {}, not the signing account {}",
+            pending.sender().to_hex_literal(),
+            account.address().to_hex_literal()
+        );
+    }
+    let options = TransactionOptions {
+        max_gas_amount: max_gas.unwrap_or_else(|| pending.max_gas_amount()),
+        gas_unit_price: replacement_gas_unit_price(pending.gas_unit_price(), gas_unit_price)?,
+        expiration_secs,
+    };
+    let builder = transaction_builder(
+        pending.payload().to_owned(),
+        pending.chain_id().id(),
+        &options,
+    );
+    let replacement = account.sign_with_transaction_builder(builder);
+    println!(
+        "Replacing {hash} at sequence number {} with {}, gas unit price {} -> {}",
+        pending.sequence_number(),
+        replacement.clone().committed_hash(),
+        pending.gas_unit_price(),
+        options.gas_unit_price
+    );
+
+    submit_transaction::run(&replacement, false, output).await
+}
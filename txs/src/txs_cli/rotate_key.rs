@@ -0,0 +1,97 @@
+use super::{send_payload, GasArgs, SimulateArgs, TransactionArgs};
+use anyhow::{Context, Result};
+use libra_config::extension::{cli_config_ext::CliConfigExt, client_ext::ClientExt};
+use query::output::OutputFormat;
+use std::path::Path;
+use txs::{
+    crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    extension::ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    key_rotation::{rotate_key_payload, verify_rotation},
+    rest_client::Client,
+    signer::{originating_address, SignerSource},
+};
+use zapatos::common::types::{CliConfig, ConfigSearchMode, DEFAULT_PROFILE};
+
+/// Rotate the signing account to the key in a libra-wallet private-keys.yaml, then point
+/// the profile the key was loaded from at the new key
+pub async fn run(
+    signer: &SignerSource,
+    new_key_file: &Path,
+    transaction: &TransactionArgs,
+    gas: &GasArgs,
+) -> Result<()> {
+    let client = Client::default()?;
+    let (current_key, account_address) = signer.load()?;
+    // the account may have been rotated before, so its address is not always derived
+    let account_address = match account_address {
+        Some(address) => address,
+        None => originating_address(&client, &current_key).await?,
+    };
+    let (new_key, _) = SignerSource::KeyFile(new_key_file.to_owned()).load()?;
+    let new_public_key = Ed25519PublicKey::from(&new_key);
+
+    let mut account = current_key
+        .get_account_at(Some(account_address), transaction.sequence_number)
+        .await?;
+    let payload = rotate_key_payload(
+        &client,
+        account_address,
+        account.sequence_number(),
+        &current_key,
+        &new_key,
+    )
+    .await?;
+    send_payload::run(
+        &client,
+        &mut account,
+        payload,
+        gas,
+        transaction,
+        &SimulateArgs::default(),
+        OutputFormat::Table,
+    )
+    .await?;
+    if transaction.no_wait {
+        println!(
+            "Warning: the rotation was not waited for, so config.yaml was not updated. \
+            Once it commits, sign for the account with --key-file {}",
+            new_key_file.display()
+        );
+        return Ok(());
+    }
+
+    verify_rotation(&client, account_address, &new_public_key).await?;
+    println!(
+        "Account {} now uses the key {}",
+        account_address.to_hex_literal(),
+        new_public_key
+    );
+
+    match signer {
+        SignerSource::Profile(profile) => update_profile(profile.as_deref(), &new_key),
+        _ => {
+            println!(
+                "The key was not loaded from a profile, config.yaml was not updated. \
+                Sign for the account with --key-file {}",
+                new_key_file.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn update_profile(profile: Option<&str>, new_key: &Ed25519PrivateKey) -> Result<()> {
+    let profile_name = profile.unwrap_or(DEFAULT_PROFILE);
+    let mut config = CliConfig::load_ext(ConfigSearchMode::CurrentDirAndParents)?;
+    let profile = config
+        .profiles
+        .as_mut()
+        .and_then(|profiles| profiles.get_mut(profile_name))
+        .context(format!("Profile {profile_name} not found"))?;
+    profile.private_key = Some(new_key.to_owned());
+    profile.public_key = Some(Ed25519PublicKey::from(new_key));
+    // saved back where it was loaded from, which the signer searched for the same way
+    config.save_in_ext(ConfigSearchMode::CurrentDirAndParents)?;
+    println!("Profile {profile_name} updated with the new key, sign with --profile {profile_name}");
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use super::{submit_transaction, GasArgs, SimulateArgs, TransactionArgs};
+use anyhow::Result;
+use query::output::{format_output, OutputFormat};
+use txs::{
+    extension::client_ext::{ClientExt, TransactionOptions},
+    rest_client::Client,
+    types::{transaction::TransactionPayload, LocalAccount},
+};
+
+/// Sign, submit and wait for a payload, or only simulate it, applying the gas options
+/// shared by the subcommands which send a transaction
+pub async fn run(
+    client: &Client,
+    from_account: &mut LocalAccount,
+    payload: TransactionPayload,
+    gas: &GasArgs,
+    transaction: &TransactionArgs,
+    simulation: &SimulateArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let options = options(client, from_account, &payload, gas, transaction, simulation).await?;
+
+    if simulation.simulate {
+        let report = client
+            .simulate_payload(from_account, payload, &options)
+            .await?;
+        println!("{}", format_output(&report, output)?);
+        return Ok(());
+    }
+
+    let signed_trans = client.sign_payload(from_account, payload, options).await?;
+    submit_transaction::run(&signed_trans, transaction.no_wait, output).await
+}
+
+/// The transaction options given by the gas args, estimated from a simulation when asked
+pub async fn options(
+    client: &Client,
+    from_account: &LocalAccount,
+    payload: &TransactionPayload,
+    gas: &GasArgs,
+    transaction: &TransactionArgs,
+    simulation: &SimulateArgs,
+) -> Result<TransactionOptions> {
+    let options = gas.options(transaction);
+    if !simulation.estimate_gas {
+        return Ok(options);
+    }
+
+    let options = client
+        .estimate_gas(
+            from_account,
+            payload.clone(),
+            gas.max_gas,
+            gas.gas_unit_price,
+            options,
+        )
+        .await?;
+    eprintln!(
+        "Estimated max gas: {}, gas unit price: {}",
+        options.max_gas_amount, options.gas_unit_price
+    );
+    Ok(options)
+}
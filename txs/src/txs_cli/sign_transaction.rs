@@ -0,0 +1,43 @@
+use anyhow::{bail, Result};
+use std::path::Path;
+use txs::{
+    crypto::ed25519::Ed25519PublicKey,
+    signer::SignerSource,
+    types::transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+    util::{read_transaction_file, write_transaction_file},
+};
+
+/// Offline step of offline signing: sign the transaction in `input` and write it to `output`.
+/// Does not need any network access. Also returns the warnings for the caller to show.
+pub fn run(
+    input: &Path,
+    signer: &SignerSource,
+    output: &Path,
+) -> Result<(SignedTransaction, Vec<String>)> {
+    let raw_trans: RawTransaction = read_transaction_file(input)?;
+    let (private_key, account_address) = signer.load()?;
+    let public_key = Ed25519PublicKey::from(&private_key);
+
+    // a rotated key does not derive the sender address, so only check it when we can
+    let mut warnings = vec![];
+    let signer_address = account_address
+        .unwrap_or_else(|| AuthenticationKey::ed25519(&public_key).derived_address());
+    if signer_address != raw_trans.sender() {
+        if account_address.is_some() {
+            bail!(
+                "The transaction sender {} is not the signing account {}",
+                raw_trans.sender().to_hex_literal(),
+                signer_address.to_hex_literal()
+            );
+        }
+        warnings.push(format!(
+            "The signing key does not derive the sender address {}, this is only expected if its key has been rotated",
+            raw_trans.sender().to_hex_literal()
+        ));
+    }
+
+    let signed_trans = raw_trans.sign(&private_key, public_key)?.into_inner();
+    write_transaction_file(output, &signed_trans)?;
+    eprintln!("Signed transaction written to {}", output.display());
+    Ok((signed_trans, warnings))
+}
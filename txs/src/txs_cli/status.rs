@@ -0,0 +1,29 @@
+use super::progress;
+use anyhow::Result;
+use libra_config::extension::client_ext::ClientExt;
+use query::output::{format_output, OutputFormat};
+use std::time::Duration;
+use txs::{
+    constant::{STATUS_NOT_FOUND_TIMEOUT_SECS, STATUS_POLL_INTERVAL_MILLIS},
+    rest_client::Client,
+    status::{parse_hash, wait_for_status},
+};
+
+/// Poll a transaction until it commits or expires, then show its version, gas and VM status
+pub async fn run(hash: &str, output: OutputFormat) -> Result<()> {
+    let client = Client::default()?;
+    let hash = parse_hash(hash)?;
+
+    let spinner = progress::spinner(format!("Waiting for {}", hash.to_hex_literal()));
+    let status = wait_for_status(
+        &client,
+        hash,
+        Duration::from_millis(STATUS_POLL_INTERVAL_MILLIS),
+        Duration::from_secs(STATUS_NOT_FOUND_TIMEOUT_SECS),
+    )
+    .await;
+    spinner.finish_and_clear();
+
+    println!("{}", format_output(&status?, output)?);
+    Ok(())
+}
@@ -1,10 +1,39 @@
-use anyhow::Result;
-use libra_config::extension::client_ext::ClientExt;
-use txs::{rest_client::Client, types::transaction::SignedTransaction};
+use super::progress;
+use anyhow::{Context, Result};
+use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
+use query::output::{format_output, OutputFormat};
+use txs::{
+    extension::client_ext::ClientExt, rest_client::Client, status::TxStatus,
+    types::transaction::SignedTransaction,
+};
 
-pub async fn run(signed_trans: &SignedTransaction) -> Result<()> {
+/// Submit and print the hash right away, then wait for the transaction to commit unless
+/// `no_wait`, leaving it to `txs status`
+pub async fn run(
+    signed_trans: &SignedTransaction,
+    no_wait: bool,
+    output: OutputFormat,
+) -> Result<()> {
     let client = Client::default()?;
-    let pending_trans = client.submit(signed_trans).await?.into_inner();
-    client.wait_for_transaction(&pending_trans).await?;
+    client
+        .submit(signed_trans)
+        .await
+        .context("Failed to submit the transaction")?;
+    let hash = signed_trans.clone().committed_hash();
+    // on stderr, to keep stdout parsable with --output json or yaml
+    eprintln!("Submitted {}", hash.to_hex_literal());
+    if no_wait {
+        eprintln!("Follow it with: txs status {}", hash.to_hex_literal());
+        return Ok(());
+    }
+
+    let spinner = progress::spinner("Waiting for the transaction to commit".to_owned());
+    let committed = client.wait_for_signed_transaction_ext(signed_trans).await;
+    spinner.finish_and_clear();
+
+    println!(
+        "{}",
+        format_output(&TxStatus::new(hash, &committed?)?, output)?
+    );
     Ok(())
 }
@@ -0,0 +1,129 @@
+use super::{send_payload, GasArgs, SimulateArgs, TransactionArgs};
+use anyhow::Result;
+use clap::ValueEnum;
+use dialoguer::Confirm;
+use libra_config::extension::client_ext::ClientExt;
+use libra_wallet::validator_files::validator_home_dir;
+use query::{
+    output::{format_output, OutputFormat},
+    val_config::{get_val_config, DecodedAddress},
+};
+use std::path::PathBuf;
+use txs::{
+    rest_client::Client,
+    signer::SignerSource,
+    types::network_address::NetworkAddress,
+    validator::{address_diff, get_validator_state, ValidatorFiles, ValidatorRole},
+};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ValidatorAction {
+    /// Create the stake pool and validator config from operator.yaml
+    Initialize,
+    /// Set the network addresses to the hosts of operator.yaml with the keys of
+    /// public-keys.yaml, after showing how they differ from the addresses on chain
+    UpdateAddresses,
+    /// Join the validator set from the next epoch
+    Join,
+    /// Leave the validator set from the next epoch
+    Leave,
+}
+
+pub async fn run(
+    action: ValidatorAction,
+    home_dir: Option<PathBuf>,
+    signer: &SignerSource,
+    transaction: &TransactionArgs,
+    gas: &GasArgs,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    let home_dir = validator_home_dir(home_dir)?;
+    let files = ValidatorFiles::read(&home_dir)?;
+    let client = Client::default()?;
+
+    // checked before showing a diff to confirm
+    let mut account = signer.get_account(transaction.sequence_number).await?;
+    let role = match action {
+        ValidatorAction::Initialize => ValidatorRole::Owner,
+        _ => ValidatorRole::Operator,
+    };
+    files.check_signer(role, account.address())?;
+
+    if let ValidatorAction::UpdateAddresses = action {
+        let onchain = get_val_config(&client, files.pool_address()).await?;
+        let diff = [
+            address_diff(
+                "validator_network_addresses",
+                &onchain_multiaddrs(&onchain.validator_network_addresses),
+                &local_multiaddrs(&files.validator_network_addresses()?),
+            ),
+            address_diff(
+                "fullnode_network_addresses",
+                &onchain_multiaddrs(&onchain.fullnode_network_addresses),
+                &local_multiaddrs(&files.fullnode_network_addresses()?),
+            ),
+        ]
+        .concat();
+        if diff.is_empty() {
+            println!("The addresses on chain already match the local files");
+            return Ok(());
+        }
+        println!("{}", diff.join("\n"));
+        if !yes
+            && !Confirm::new()
+                .with_prompt("Sign and submit the new addresses?")
+                .interact()?
+        {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    let payload = match action {
+        ValidatorAction::Initialize => files.initialize_validator_payload()?,
+        ValidatorAction::UpdateAddresses => files.update_network_addresses_payload()?,
+        ValidatorAction::Join => files.join_validator_set_payload()?,
+        ValidatorAction::Leave => files.leave_validator_set_payload()?,
+    };
+
+    send_payload::run(
+        &client,
+        &mut account,
+        payload,
+        gas,
+        transaction,
+        &SimulateArgs::default(),
+        output,
+    )
+    .await?;
+    if transaction.no_wait {
+        return Ok(());
+    }
+
+    // read back the stake state the transaction changed
+    let pool_address = files.pool_address();
+    match action {
+        ValidatorAction::Initialize | ValidatorAction::UpdateAddresses => {
+            let mut config = get_val_config(&client, pool_address).await?;
+            config.compare_with_local(&home_dir)?;
+            println!("{}", format_output(&config, output)?);
+        }
+        ValidatorAction::Join | ValidatorAction::Leave => {
+            let state = get_validator_state(&client, pool_address).await?;
+            println!("{}", format_output(&state, output)?);
+        }
+    }
+    Ok(())
+}
+
+fn onchain_multiaddrs(addresses: &[DecodedAddress]) -> Vec<String> {
+    addresses
+        .iter()
+        .map(|addr| addr.multiaddr.to_owned())
+        .collect()
+}
+
+fn local_multiaddrs(addresses: &[NetworkAddress]) -> Vec<String> {
+    addresses.iter().map(|addr| addr.to_string()).collect()
+}
@@ -1,5 +1,6 @@
 use anyhow::{bail, Context, Result};
-use std::fmt::{Debug, Display};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
 use zapatos_sdk::{
     move_types::identifier::Identifier,
     types::{account_address::AccountAddress, transaction::SignedTransaction},
@@ -22,21 +23,6 @@ pub fn format_signed_transaction(signed_trans: &SignedTransaction) -> String {
     format!("{raw_trans}\n{authenticator}")
 }
 
-pub fn format_type_args<T: Display>(type_args: &[T]) -> String {
-    format!(
-        "Type Arguments: [{}]",
-        type_args
-            .iter()
-            .map(|t| t.to_string())
-            .collect::<Vec<_>>()
-            .join(", ")
-    )
-}
-
-pub fn format_args<T: Debug>(args: &[T]) -> String {
-    format!("Arguments: {args:#?}")
-}
-
 pub fn parse_function_id(function_id: &str) -> Result<(AccountAddress, Identifier, Identifier)> {
     let id_parts = function_id
         .split("::")
@@ -53,3 +39,29 @@ pub fn parse_function_id(function_id: &str) -> Result<(AccountAddress, Identifie
         .context(format!("Failed to parse function name: {}", id_parts[2]))?;
     Ok((module_address, module_name, function_name))
 }
+
+/// Write a raw or signed transaction to a file, as json if the file has a `.json` extension
+/// and as BCS otherwise
+pub fn write_transaction_file<T: Serialize>(path: &Path, transaction: &T) -> Result<()> {
+    let bytes = if is_json_file(path) {
+        serde_json::to_vec_pretty(transaction)?
+    } else {
+        bcs::to_bytes(transaction)?
+    };
+    std::fs::write(path, bytes).context(format!("Failed to write {}", path.display()))
+}
+
+/// Read a raw or signed transaction written by `write_transaction_file`
+pub fn read_transaction_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let bytes = std::fs::read(path).context(format!("Failed to read {}", path.display()))?;
+    if is_json_file(path) {
+        serde_json::from_slice(&bytes)
+            .context(format!("Invalid json transaction file {}", path.display()))
+    } else {
+        bcs::from_bytes(&bytes).context(format!("Invalid BCS transaction file {}", path.display()))
+    }
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "json").unwrap_or(false)
+}
@@ -0,0 +1,244 @@
+//! stake and validator config transactions driven by the operator.yaml, owner.yaml and
+//! public-keys.yaml written by libra-wallet
+
+use anyhow::{bail, Context, Result};
+use libra_wallet::{
+    keys::PUBLIC_KEYS_FILE,
+    utils::{from_yaml, read_from_file, read_public_identity_file},
+    validator_files::{OPERATOR_FILE, OWNER_FILE},
+};
+use serde::Serialize;
+use std::path::Path;
+use zapatos_genesis::{
+    config::{OperatorConfiguration, OwnerConfiguration},
+    keys::PublicIdentity,
+};
+use zapatos_sdk::{
+    crypto::ValidCryptoMaterial,
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::{aptos_api_types::ViewRequest, Client},
+    types::{
+        account_address::AccountAddress,
+        network_address::NetworkAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+
+/// Whose account a stake transaction has to be signed with
+#[derive(Clone, Copy, Debug)]
+pub enum ValidatorRole {
+    /// creates the stake pool with its funds
+    Owner,
+    /// runs the validator node for the owner
+    Operator,
+}
+
+pub struct ValidatorFiles {
+    pub operator: OperatorConfiguration,
+    pub owner: OwnerConfiguration,
+    pub public_identity: PublicIdentity,
+}
+
+impl ValidatorFiles {
+    pub fn read(home_dir: &Path) -> Result<Self> {
+        let public_keys_file = home_dir.join(PUBLIC_KEYS_FILE);
+        Ok(Self {
+            operator: read_yaml(&home_dir.join(OPERATOR_FILE))?,
+            owner: read_yaml(&home_dir.join(OWNER_FILE))?,
+            public_identity: read_public_identity_file(&public_keys_file)
+                .context(format!("Failed to read {}", public_keys_file.display()))?,
+        })
+    }
+
+    /// The stake pool lives at the owner's account
+    pub fn pool_address(&self) -> AccountAddress {
+        self.owner.owner_account_address.into()
+    }
+
+    /// Only the account of `role` in owner.yaml or operator.yaml can send the transaction
+    pub fn check_signer(&self, role: ValidatorRole, signer: AccountAddress) -> Result<()> {
+        let (name, expected): (_, AccountAddress) = match role {
+            ValidatorRole::Owner => ("owner", self.owner.owner_account_address.into()),
+            ValidatorRole::Operator => ("operator", self.operator.operator_account_address.into()),
+        };
+        if signer != expected {
+            bail!(
+                "The {name} of pool {} is {}, not the signing account {}",
+                self.pool_address().to_hex_literal(),
+                expected.to_hex_literal(),
+                signer.to_hex_literal()
+            );
+        }
+        Ok(())
+    }
+
+    /// The hosts of operator.yaml with the x25519 keys of public-keys.yaml
+    pub fn validator_network_addresses(&self) -> Result<Vec<NetworkAddress>> {
+        let key = self
+            .public_identity
+            .validator_network_public_key
+            .context(format!("{PUBLIC_KEYS_FILE} has no validator network key"))?;
+        Ok(vec![self
+            .operator
+            .validator_host
+            .as_network_address(key)?])
+    }
+
+    pub fn fullnode_network_addresses(&self) -> Result<Vec<NetworkAddress>> {
+        match (
+            &self.operator.full_node_host,
+            self.public_identity.full_node_network_public_key,
+        ) {
+            (Some(host), Some(key)) => Ok(vec![host.as_network_address(key)?]),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// `0x1::stake::initialize_validator`, creating the stake pool and validator config
+    pub fn initialize_validator_payload(&self) -> Result<TransactionPayload> {
+        stake_payload(
+            "initialize_validator",
+            vec![
+                bcs::to_bytes(&self.operator.consensus_public_key.to_bytes().to_vec())?,
+                bcs::to_bytes(
+                    &self
+                        .operator
+                        .consensus_proof_of_possession
+                        .to_bytes()
+                        .to_vec(),
+                )?,
+                bcs::to_bytes(&bcs::to_bytes(&self.validator_network_addresses()?)?)?,
+                bcs::to_bytes(&bcs::to_bytes(&self.fullnode_network_addresses()?)?)?,
+            ],
+        )
+    }
+
+    /// `0x1::stake::update_network_and_fullnode_addresses`
+    pub fn update_network_addresses_payload(&self) -> Result<TransactionPayload> {
+        update_network_addresses_payload(
+            self.pool_address(),
+            &self.validator_network_addresses()?,
+            &self.fullnode_network_addresses()?,
+        )
+    }
+
+    /// `0x1::stake::join_validator_set`
+    pub fn join_validator_set_payload(&self) -> Result<TransactionPayload> {
+        stake_payload(
+            "join_validator_set",
+            vec![bcs::to_bytes(&self.pool_address())?],
+        )
+    }
+
+    /// `0x1::stake::leave_validator_set`
+    pub fn leave_validator_set_payload(&self) -> Result<TransactionPayload> {
+        stake_payload(
+            "leave_validator_set",
+            vec![bcs::to_bytes(&self.pool_address())?],
+        )
+    }
+}
+
+/// The addresses are passed to Move as the BCS bytes of the address lists
+pub fn update_network_addresses_payload(
+    pool_address: AccountAddress,
+    validator_addresses: &[NetworkAddress],
+    fullnode_addresses: &[NetworkAddress],
+) -> Result<TransactionPayload> {
+    stake_payload(
+        "update_network_and_fullnode_addresses",
+        vec![
+            bcs::to_bytes(&pool_address)?,
+            bcs::to_bytes(&bcs::to_bytes(validator_addresses)?)?,
+            bcs::to_bytes(&bcs::to_bytes(fullnode_addresses)?)?,
+        ],
+    )
+}
+
+/// The lines of a diff between the addresses on chain and the ones to submit, empty
+/// when they are the same
+pub fn address_diff(field: &str, onchain: &[String], local: &[String]) -> Vec<String> {
+    if onchain == local {
+        return vec![];
+    }
+    let mut lines = vec![format!("{field}:")];
+    lines.extend(onchain.iter().map(|addr| format!("  - {addr}")));
+    lines.extend(local.iter().map(|addr| format!("  + {addr}")));
+    lines
+}
+
+fn stake_payload(function: &str, args: Vec<Vec<u8>>) -> Result<TransactionPayload> {
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("stake")?),
+        Identifier::new(function)?,
+        vec![],
+        args,
+    )))
+}
+
+pub(crate) fn read_yaml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents = String::from_utf8(
+        read_from_file(path).context(format!("Failed to read {}", path.display()))?,
+    )?;
+    from_yaml(&contents).context(format!("Failed to parse {}", path.display()))
+}
+
+/// Where a stake pool is in the validator set lifecycle
+#[derive(Debug, Serialize)]
+pub struct ValidatorState {
+    pub pool_address: String,
+    pub state: String,
+}
+
+/// Read the state of a stake pool with the `0x1::stake::get_validator_state` view function
+pub async fn get_validator_state(
+    client: &Client,
+    pool_address: AccountAddress,
+) -> Result<ValidatorState> {
+    let request = ViewRequest {
+        function: "0x1::stake::get_validator_state".parse()?,
+        type_arguments: vec![],
+        arguments: vec![serde_json::Value::String(pool_address.to_hex_literal())],
+    };
+    let response = client
+        .view(&request, None)
+        .await
+        .context("Failed to get the validator state")?
+        .into_inner();
+    let state = response
+        .first()
+        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+        .context("Unexpected validator state response")?;
+
+    Ok(ValidatorState {
+        pool_address: pool_address.to_hex_literal(),
+        state: match state {
+            1 => "pending_active",
+            2 => "active",
+            3 => "pending_inactive",
+            4 => "inactive",
+            _ => "unknown",
+        }
+        .to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_changed_addresses() {
+        let old = vec!["/ip4/1.2.3.4/tcp/6180".to_owned()];
+        let new = vec!["/ip4/5.6.7.8/tcp/6180".to_owned()];
+        assert!(address_diff("validator", &old, &old).is_empty());
+        assert_eq!(
+            address_diff("validator", &old, &new),
+            vec![
+                "validator:",
+                "  - /ip4/1.2.3.4/tcp/6180",
+                "  + /ip4/5.6.7.8/tcp/6180"
+            ]
+        );
+    }
+}
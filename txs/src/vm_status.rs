@@ -0,0 +1,156 @@
+//! explain why a transaction failed, decoding Move aborts with the framework error map
+
+use move_binary_format::access::ModuleAccess;
+use serde::Serialize;
+use std::fmt;
+use zapatos_framework::{get_metadata_from_compiled_module, ReleaseBundle};
+use zapatos_sdk::{
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::aptos_api_types::UserTransaction,
+    types::account_address::AccountAddress,
+};
+
+#[derive(Debug, Serialize)]
+pub struct TxFailure {
+    pub version: u64,
+    pub hash: String,
+    pub gas_used: u64,
+    pub vm_status: String,
+    pub abort: Option<AbortInfo>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AbortInfo {
+    pub module: String,
+    pub abort_code: u64,
+    /// std::error category, the upper bits of the abort code
+    pub category: String,
+    pub reason_name: Option<String>,
+    pub reason_description: Option<String>,
+}
+
+impl TxFailure {
+    pub fn new(user_trans: &UserTransaction) -> Self {
+        let vm_status = user_trans.info.vm_status.to_owned();
+        Self {
+            version: user_trans.info.version.0,
+            hash: user_trans.info.hash.to_string(),
+            gas_used: user_trans.info.gas_used.0,
+            abort: decode_abort(&vm_status, zapatos_cached_packages::head_release_bundle()),
+            vm_status,
+        }
+    }
+}
+
+impl fmt::Display for TxFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction failed at version {}", self.version)?;
+        writeln!(f, "Hash: {}", self.hash)?;
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        writeln!(f, "VM status: {}", self.vm_status)?;
+        if let Some(abort) = &self.abort {
+            writeln!(f, "Module: {}", abort.module)?;
+            writeln!(f, "Abort code: {:#x}", abort.abort_code)?;
+            writeln!(f, "Category: {}", abort.category)?;
+            if let Some(name) = &abort.reason_name {
+                writeln!(f, "Reason: {name}")?;
+            }
+            if let Some(description) = &abort.reason_description {
+                writeln!(f, "Description: {description}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TxFailure {}
+
+/// Decode a vm status such as `Move abort in 0x1::coin: 0x10006`, looking up the
+/// reason in the error map of the module's metadata
+pub fn decode_abort(vm_status: &str, framework: &ReleaseBundle) -> Option<AbortInfo> {
+    let (module, abort_code) = parse_abort(vm_status)?;
+    let reason = abort_code & 0xffff;
+    let description = framework
+        .compiled_modules()
+        .iter()
+        .find(|compiled| compiled.self_id() == module)
+        .and_then(get_metadata_from_compiled_module)
+        .and_then(|metadata| metadata.error_map.get(&reason).cloned());
+
+    Some(AbortInfo {
+        module: format!("{}::{}", module.address().to_hex_literal(), module.name()),
+        abort_code,
+        category: error_category(abort_code >> 16).to_owned(),
+        reason_name: description.as_ref().map(|d| d.code_name.to_owned()),
+        reason_description: description.map(|d| d.code_description),
+    })
+}
+
+/// The node reports either the bare code or `NAME(code): description`
+fn parse_abort(vm_status: &str) -> Option<(ModuleId, u64)> {
+    let rest = vm_status.strip_prefix("Move abort in ")?;
+    let (location, code) = rest.split_once(": ")?;
+    let (address, name) = location.split_once("::")?;
+    let module = ModuleId::new(
+        AccountAddress::from_hex_literal(address).ok()?,
+        Identifier::new(name).ok()?,
+    );
+
+    let code = match code.split_once('(') {
+        Some((_, with_name)) => with_name.split_once(')')?.0,
+        None => code.trim(),
+    };
+    let abort_code = u64::from_str_radix(code.trim_start_matches("0x"), 16).ok()?;
+    Some((module, abort_code))
+}
+
+/// Categories from the std::error module
+fn error_category(category: u64) -> &'static str {
+    match category {
+        0x1 => "INVALID_ARGUMENT",
+        0x2 => "OUT_OF_RANGE",
+        0x3 => "INVALID_STATE",
+        0x4 => "UNAUTHENTICATED",
+        0x5 => "PERMISSION_DENIED",
+        0x6 => "NOT_FOUND",
+        0x7 => "ABORTED",
+        0x8 => "ALREADY_EXISTS",
+        0x9 => "RESOURCE_EXHAUSTED",
+        0xA => "CANCELLED",
+        0xB => "INTERNAL",
+        0xC => "NOT_IMPLEMENTED",
+        0xD => "UNAVAILABLE",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_abort_status() {
+        let (module, code) = parse_abort("Move abort in 0x1::coin: 0x10006").unwrap();
+        assert_eq!(module.name().as_str(), "coin");
+        assert_eq!(code, 0x10006);
+
+        let (_, code) = parse_abort(
+            "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins to complete transaction",
+        )
+        .unwrap();
+        assert_eq!(code, 0x10006);
+
+        assert!(parse_abort("Out of gas").is_none());
+    }
+
+    #[test]
+    fn decodes_framework_reason() {
+        let abort = decode_abort(
+            "Move abort in 0x1::coin: 0x10006",
+            zapatos_cached_packages::head_release_bundle(),
+        )
+        .unwrap();
+        assert_eq!(abort.category, "INVALID_ARGUMENT");
+        assert_eq!(abort.reason_name.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+    }
+}